@@ -24,14 +24,21 @@
           nativeBuildInputs = with pkgs; [
             pkg-config
             makeWrapper
+            installShellFiles
           ];
 
           buildInputs = with pkgs; [
             edid-decode
           ];
 
-          # Make edid-decode available at runtime
-          postInstall = ''
+          # Make edid-decode available at runtime, and install completions
+          # (generated by running the binary, so not when cross compiling)
+          postInstall = pkgs.lib.optionalString (pkgs.stdenv.buildPlatform.canExecute pkgs.stdenv.hostPlatform) ''
+            installShellCompletion --cmd console-mode \
+              --bash <($out/bin/console-mode --generate-completions bash) \
+              --zsh <($out/bin/console-mode --generate-completions zsh) \
+              --fish <($out/bin/console-mode --generate-completions fish)
+          '' + ''
             wrapProgram $out/bin/console-mode \
               --prefix PATH : ${pkgs.lib.makeBinPath [ pkgs.edid-decode ]}
           '';
@@ -1,8 +1,10 @@
 { lib
+, stdenv
 , rustPlatform
 , pkg-config
 , edid-decode
 , makeWrapper
+, installShellFiles
 }:
 
 rustPlatform.buildRustPackage {
@@ -18,14 +20,21 @@ rustPlatform.buildRustPackage {
   nativeBuildInputs = [
     pkg-config
     makeWrapper
+    installShellFiles
   ];
 
   buildInputs = [
     edid-decode
   ];
 
-  # Make edid-decode available at runtime
-  postInstall = ''
+  # Make edid-decode available at runtime, and install completions (generated
+  # by running the binary, so not when cross compiling)
+  postInstall = lib.optionalString (stdenv.buildPlatform.canExecute stdenv.hostPlatform) ''
+    installShellCompletion --cmd console-mode \
+      --bash <($out/bin/console-mode --generate-completions bash) \
+      --zsh <($out/bin/console-mode --generate-completions zsh) \
+      --fish <($out/bin/console-mode --generate-completions fish)
+  '' + ''
     wrapProgram $out/bin/console-mode \
       --prefix PATH : ${lib.makeBinPath [ edid-decode ]}
   '';
@@ -0,0 +1,64 @@
+//! Capabilities cached by EDID, so edid-decode only runs for displays not seen before
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::edid::DisplayCapabilities;
+
+/// Part of the file name; bump it when `DisplayCapabilities` or the EDID
+/// parsing changes so stale entries are ignored
+const CACHE_VERSION: u32 = 5;
+
+/// Capabilities cached for exactly these EDID bytes, if any
+pub fn load_capabilities(edid: &[u8]) -> Option<DisplayCapabilities> {
+    let contents = fs::read_to_string(cache_path(edid)?).ok()?;
+    toml::from_str(&contents).ok()
+}
+
+/// Cache the capabilities parsed from these EDID bytes
+pub fn save_capabilities(edid: &[u8], caps: &DisplayCapabilities) -> Result<()> {
+    let path = cache_path(edid).context("Could not determine cache directory")?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .context(format!("Failed to create cache directory {}", dir.display()))?;
+    }
+
+    let contents = toml::to_string(caps).context("Failed to serialize capabilities")?;
+    fs::write(&path, contents)
+        .context(format!("Failed to write {}", path.display()))
+}
+
+/// `$XDG_CACHE_HOME/console-mode`, defaulting to `~/.cache/console-mode`
+pub fn cache_dir() -> Option<PathBuf> {
+    let cache_home = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+    };
+    Some(cache_home.join("console-mode"))
+}
+
+/// One file per EDID, so a different monitor never hits another's entry
+fn cache_path(edid: &[u8]) -> Option<PathBuf> {
+    Some(cache_dir()?.join(format!("edid-v{}-{:016x}.toml", CACHE_VERSION, fnv1a(edid))))
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is the same in every Rust release
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_matches_reference_values() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+}
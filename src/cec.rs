@@ -0,0 +1,25 @@
+use anyhow::{anyhow, Result};
+use cec_rs::{CecConnectionCfgBuilder, CecDeviceType, CecDeviceTypeVec, CecLogicalAddress};
+
+/// Wake the TV (and any AVR following it) and claim the active source, so
+/// it switches to our input before gamescope starts drawing
+pub fn power_on() -> Result<()> {
+    // No port given, so libcec picks the first adapter it detects
+    let connection = CecConnectionCfgBuilder::default()
+        .device_name("console-mode".to_string())
+        .device_types(CecDeviceTypeVec::new(CecDeviceType::PlaybackDevice))
+        .activate_source(true)
+        .build()
+        .map_err(|e| anyhow!("Invalid CEC configuration: {}", e))?
+        .open()
+        .map_err(|e| anyhow!("Failed to open CEC adapter: {:?}", e))?;
+
+    connection
+        .send_power_on_devices(CecLogicalAddress::Tv)
+        .map_err(|e| anyhow!("Failed to send CEC power on: {:?}", e))?;
+
+    // "Image View On" alone leaves some TVs on their last input
+    connection
+        .set_active_source(CecDeviceType::PlaybackDevice)
+        .map_err(|e| anyhow!("Failed to send CEC active source: {:?}", e))
+}
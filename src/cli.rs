@@ -0,0 +1,563 @@
+use clap::{Parser, ValueEnum, ValueHint};
+use clap_complete::Shell;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use crate::controller::ControllerLayout;
+use crate::theme::ThemeName;
+
+/// Console Mode - A gamescope session launcher with automatic display detection
+#[derive(Parser, Debug, Clone)]
+#[command(author, version, about, long_about = None)]
+pub struct Args {
+    /// Override display selection: connector name (e.g., "card1-HDMI-A-1" or "HDMI-1") or part of the EDID monitor name or serial (e.g., "LG TV")
+    #[arg(short, long)]
+    pub display: Option<String>,
+
+    /// Override resolution, optionally with a refresh rate (e.g., "1920x1080" or "2560x1440@120")
+    #[arg(short, long)]
+    pub resolution: Option<String>,
+
+    /// Use a resolution every connected display supports instead of asking which display to use (see README for limits)
+    #[arg(long, conflicts_with_all = ["tui_launcher", "headless", "resolution"])]
+    pub mirror: bool,
+
+    /// Render games at this resolution and upscale to the output (e.g., "2560x1440")
+    #[arg(long)]
+    pub render_resolution: Option<String>,
+
+    /// Override refresh rate in Hz; fractional rates like 59.94 are accepted
+    #[arg(short = 'f', long, value_parser = parse_refresh_rate)]
+    pub refresh_rate: Option<f32>,
+
+    /// Clamp --refresh-rate to the display's detected maximum (always done for the Sunshine client FPS)
+    #[arg(long)]
+    pub clamp_refresh: bool,
+
+    /// Force enable VRR/Adaptive Sync
+    #[arg(long)]
+    pub force_vrr: bool,
+
+    /// Force enable HDR
+    #[arg(long)]
+    pub force_hdr: bool,
+
+    /// Disable VRR even if supported
+    #[arg(long)]
+    pub no_vrr: bool,
+
+    /// Disable HDR even if supported
+    #[arg(long)]
+    pub no_hdr: bool,
+
+    /// Bits per color to drive the display at (8, 10 or 12), capped at what it supports; HDR asks for 10 by default
+    #[arg(long, value_parser = parse_bpc)]
+    pub bpc: Option<u32>,
+
+    /// Rotate the output for a monitor mounted on its side: 90 (left side up), 180 (upside down) or 270 (right side up); defaults to the panel orientation DRM reports
+    #[arg(long, value_name = "DEGREES", value_parser = parse_rotation)]
+    pub rotate: Option<u32>,
+
+    /// Never drive the display at more than this many bits per color (8, 10 or 12), even with --force-bpc
+    #[arg(long, value_name = "BPC", value_parser = parse_bpc)]
+    pub max_bpc: Option<u32>,
+
+    /// Use --bpc even if the display doesn't report supporting it
+    #[arg(long, requires = "bpc")]
+    pub force_bpc: bool,
+
+    /// HDMI color format; auto switches to YCbCr 4:2:0 when RGB needs more bandwidth than the HDMI link has and the display takes the mode in 4:2:0 (amdgpu only)
+    #[arg(long, value_enum, default_value_t = ColorFormat::Auto)]
+    pub color_format: ColorFormat,
+
+    /// Steam Deck preset: use the internal panel unless --display is given, add
+    /// Deck gamescope args and run Steam with its Deck UI
+    #[arg(long)]
+    pub steam_deck_mode: bool,
+
+    /// Use safe mode (disable advanced features)
+    #[arg(long)]
+    pub safe_mode: bool,
+
+    /// Don't pass --mangoapp to gamescope (also skipped when mangoapp isn't installed)
+    #[arg(long)]
+    pub no_mangoapp: bool,
+
+    /// Don't pass -e (gamescope's --steam) to gamescope, turning off its Steam integration for --command or --no-steam sessions; gamescope's Wayland socket is still created
+    #[arg(long)]
+    pub no_expose_wayland: bool,
+
+    /// Power on the selected HDMI display over CEC before launching (needs the `cec` feature)
+    #[arg(long)]
+    pub cec_poweron: bool,
+
+    /// Power on the selected monitor over DDC/CI with ddcutil before launching (needs the `ddc` feature)
+    #[arg(long)]
+    pub ddc_poweron: bool,
+
+    /// Switch the selected monitor to this input over DDC/CI with ddcutil before launching: dp1, dp2, hdmi1, hdmi2, dvi1, dvi2, vga1, vga2 or a VCP 0x60 value like 0x0f (needs the `ddc` feature)
+    #[arg(long, value_name = "INPUT", value_parser = parse_ddc_input)]
+    pub ddc_input: Option<u8>,
+
+    /// Refuse to launch with a --resolution the display doesn't list as supported
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Run gamescope on a virtual output (no monitor needed), sized from --resolution/--refresh-rate or the Sunshine client
+    #[arg(long, conflicts_with_all = ["display", "tui_launcher", "idle_watcher", "force_nested"])]
+    pub headless: bool,
+
+    /// Run gamescope on the DRM backend even if another compositor seems to be running
+    #[arg(long, conflicts_with = "force_nested")]
+    pub force_native: bool,
+
+    /// Run gamescope nested inside the current Wayland/X11 session
+    #[arg(long)]
+    pub force_nested: bool,
+
+    /// Run gamescope in a window instead of fullscreen (for testing on a desktop)
+    #[arg(long)]
+    pub windowed: bool,
+
+    /// Make the --windowed gamescope window borderless
+    #[arg(long, requires = "windowed")]
+    pub borderless: bool,
+
+    /// Keep the mouse cursor inside the gamescope window (gamescope --force-grab-cursor), for games that lose it nested
+    #[arg(long)]
+    pub grab_cursor: bool,
+
+    /// Flip frames immediately instead of waiting for vblank, trading tearing for latency (gamescope --immediate-flips)
+    #[arg(long)]
+    pub immediate_flips: bool,
+
+    /// Always composite instead of scanning games out directly (gamescope --force-composition), for flicker or overlay issues
+    #[arg(long)]
+    pub force_composition: bool,
+
+    /// How gamescope scales the game's image to the output (gamescope --scaler)
+    #[arg(long, value_enum, value_name = "MODE")]
+    pub scaling: Option<ScalingMode>,
+
+    /// Upscaling filter (gamescope --filter); fsr and nis need a lower game resolution
+    #[arg(long, value_enum)]
+    pub filter: Option<ScalingFilter>,
+
+    /// Print the gamescope command that would be run, without running it
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Pipe gamescope's stderr through console-mode: into the debug log (the state directory's debug.log unless --log-file or --verbose say otherwise), to the terminal (unless --quiet), and its last lines shown if gamescope fails to start
+    #[arg(long)]
+    pub capture_gamescope: bool,
+
+    /// Start gamescope with `true` instead of Steam to check it accepts the display and settings, then exit
+    #[arg(long, conflicts_with_all = ["dry_run", "command", "no_steam", "steam_appid", "startup_timeout", "daemon"])]
+    pub test_launch: bool,
+
+    /// Install a display manager session that runs console-mode with the other args given, then exit
+    #[arg(long)]
+    pub install_session: bool,
+
+    /// Where --install-session puts bin/ and share/wayland-sessions/
+    #[arg(long, value_name = "DIR", default_value = "/usr/local", value_hint = ValueHint::DirPath)]
+    pub prefix: PathBuf,
+
+    /// Only use this GPU and the displays on it (e.g., "card1" or "/dev/dri/renderD128")
+    #[arg(long)]
+    pub gpu: Option<String>,
+
+    /// Custom gamescope binary path
+    #[arg(long, value_hint = ValueHint::ExecutablePath)]
+    pub gamescope_bin: Option<PathBuf>,
+
+    /// Custom steam binary path
+    #[arg(long, value_hint = ValueHint::ExecutablePath)]
+    pub steam_bin: Option<PathBuf>,
+
+    /// Additional steam arguments
+    #[arg(long, value_delimiter = ' ', num_args = 1..)]
+    pub steam_args: Vec<String>,
+
+    /// Run this command in gamescope instead of Steam Big Picture (e.g., "lutris" or "kodi --standalone")
+    #[arg(long, value_delimiter = ' ', num_args = 1.., conflicts_with_all = ["steam_bin", "steam_args"], value_hint = ValueHint::CommandName)]
+    pub command: Vec<String>,
+
+    /// Start gamescope with no program in it (unless --command), for Wayland clients to attach to its socket
+    #[arg(long, conflicts_with_all = ["steam_bin", "steam_args"])]
+    pub no_steam: bool,
+
+    /// Steam flag that opens Big Picture (e.g. "-gamepadui" on some Steam branches; "" for none)
+    #[arg(long, value_name = "FLAG", default_value = "-bigpicture", allow_hyphen_values = true,
+          conflicts_with_all = ["command", "no_steam", "steam_appid"])]
+    pub bigpicture_flag: String,
+
+    /// Launch this Steam game (by numeric AppID) instead of Big Picture
+    #[arg(long, value_name = "ID", conflicts_with_all = ["command", "no_steam"])]
+    pub steam_appid: Option<u32>,
+
+    /// Set an environment variable for gamescope and the game session (repeatable)
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_env_var)]
+    pub env: Vec<(String, String)>,
+
+    /// Also list writeback and virtual connectors (e.g. virtio-gpu's Virtual-1 in a VM)
+    #[arg(long)]
+    pub include_virtual: bool,
+
+    /// Ignore built-in panels (eDP, LVDS, DSI), e.g. to always use a laptop's external monitor
+    #[arg(long, conflicts_with = "internal_only")]
+    pub external_only: bool,
+
+    /// Only consider built-in panels (eDP, LVDS, DSI)
+    #[arg(long)]
+    pub internal_only: bool,
+
+    /// Order in which detected displays are listed
+    #[arg(long, value_enum, default_value_t = SortOrder::Name)]
+    pub sort_displays: SortOrder,
+
+    /// Launcher command for display selection (e.g., "dmenu", "rofi -dmenu", "wofi --dmenu", "fzf")
+    #[arg(long, value_hint = ValueHint::CommandString)]
+    pub launcher: Option<String>,
+
+    /// Send plain text lines to rofi/wofi instead of markup with display icons
+    #[arg(long, requires = "launcher")]
+    pub launcher_plain: bool,
+
+    /// What to do when the launcher is closed without picking a display
+    #[arg(long, value_enum, default_value_t = OnCancel::Abort, requires = "launcher")]
+    pub on_cancel: OnCancel,
+
+    /// Launch TUI monitor selector with controller support
+    #[arg(long)]
+    pub tui_launcher: bool,
+
+    /// Remember the display picked in the TUI and pre-select it next time (default)
+    #[arg(long, overrides_with = "no_remember_display")]
+    pub remember_display: bool,
+
+    /// Don't remember the display picked in the TUI
+    #[arg(long)]
+    pub no_remember_display: bool,
+
+    /// Auto-select the highlighted display in the TUI after this many seconds without input (0 disables)
+    #[arg(long, value_name = "SECS")]
+    pub tui_timeout: Option<u64>,
+
+    /// Use the first display (and with --pick-refresh the highest refresh rate) if nobody answers the terminal prompt within this many seconds
+    #[arg(long, value_name = "SECS")]
+    pub select_timeout: Option<u64>,
+
+    /// Launch straight after picking in the TUI, without showing the detected capabilities first
+    #[arg(long)]
+    pub tui_no_confirm: bool,
+
+    /// Controller face button layout in the TUI [default: xbox]
+    #[arg(long, value_enum, value_name = "LAYOUT")]
+    pub controller_layout: Option<ControllerLayout>,
+
+    /// TUI color theme [default: dark]
+    #[arg(long, value_enum, value_name = "NAME")]
+    pub theme: Option<ThemeName>,
+
+    /// Rumble the controller briefly when selecting in the TUI, if it supports force feedback
+    #[arg(long)]
+    pub tui_rumble: bool,
+
+    /// Detect every display's capabilities in the background and show VRR/HDR/refresh badges in the TUI list
+    #[arg(long)]
+    pub tui_detect_all: bool,
+
+    /// Re-detect capabilities with edid-decode instead of using the cached result
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Read the --display display's EDID from this file instead of sysfs, for displays that report a broken one
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub edid_file: Option<PathBuf>,
+
+    /// Skip the pauses that leave time to read output before launching
+    #[arg(long, conflicts_with = "delay_scale")]
+    pub no_delays: bool,
+
+    /// Scale the pauses that leave time to read output (e.g. 0.5 halves them)
+    #[arg(long, value_name = "SCALE", default_value_t = 1.0, value_parser = parse_delay_scale)]
+    pub delay_scale: f32,
+
+    /// Print the detected displays and their capabilities (or just the --display one) instead of launching
+    #[arg(long, conflicts_with_all = ["tui_launcher", "idle_watcher", "headless"])]
+    pub detect_only: bool,
+
+    /// Print every refresh rate the EDID lists for each resolution instead of launching (with --detect-only, after the capabilities)
+    #[arg(long, conflicts_with_all = ["tui_launcher", "idle_watcher", "headless"])]
+    pub refresh_list: bool,
+
+    /// If no display is connected yet (e.g. a slow TV), wait up to this many seconds for one
+    #[arg(long, value_name = "SECS")]
+    pub wait_for_display: Option<u64>,
+
+    /// Time display detection, edid-decode and EDID parsing per display instead of launching
+    #[arg(long, hide = true, conflicts_with_all = ["tui_launcher", "idle_watcher", "headless", "daemon"])]
+    pub benchmark_detection: bool,
+
+    /// Copy the selected display's raw EDID to this file (and edid-decode's text to PATH.txt), e.g. to attach to a bug report
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub dump_edid: Option<PathBuf>,
+
+    /// Print --detect-only results as JSON
+    #[arg(long, requires = "detect_only")]
+    pub json: bool,
+
+    /// Ask which refresh rate to use when the EDID lists several for the resolution, instead of using the highest
+    #[arg(long, conflicts_with = "refresh_rate")]
+    pub pick_refresh: bool,
+
+    /// Count gamescope exiting within this many seconds as a failed start, and retry with safer settings without asking
+    #[arg(long, value_name = "SECS")]
+    pub startup_timeout: Option<u64>,
+
+    /// What to do when the session ends (Steam exits)
+    #[arg(long, value_enum, default_value_t = AfterLaunch::Exit)]
+    pub after_launch: AfterLaunch,
+
+    /// Shell command to run before gamescope starts, with the session in CONSOLE_MODE_* variables
+    #[arg(long, value_name = "CMD", value_hint = ValueHint::CommandString)]
+    pub pre_launch: Option<String>,
+
+    /// Shell command to run after gamescope exits, whether or not it started
+    #[arg(long, value_name = "CMD", value_hint = ValueHint::CommandString)]
+    pub post_launch: Option<String>,
+
+    /// Don't launch when the --pre-launch command fails
+    #[arg(long)]
+    pub pre_launch_required: bool,
+
+    /// Start in idle watcher mode - waits for controller input or Sunshine session
+    #[arg(long)]
+    pub idle_watcher: bool,
+
+    /// Stay running and launch on the --display connector each time it's plugged in (or already connected at startup)
+    #[arg(long, conflicts_with_all = ["tui_launcher", "idle_watcher", "headless", "detect_only", "refresh_list"])]
+    pub daemon: bool,
+
+    /// Address for --daemon's HTTP status endpoint (needs the `status-server` feature) [default: 127.0.0.1:7878]
+    #[arg(long, value_name = "ADDR", requires = "daemon")]
+    pub listen: Option<SocketAddr>,
+
+    /// Config file to use instead of $XDG_CONFIG_HOME/console-mode/config.toml
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub config: Option<PathBuf>,
+
+    /// Load a named [profiles.NAME] table from the config, underneath any CLI flags
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// List the config's named profiles and exit
+    #[arg(long)]
+    pub list_profiles: bool,
+
+    /// Print a completion script for this shell to stdout and exit
+    #[arg(long, value_name = "SHELL")]
+    pub generate_completions: Option<Shell>,
+
+    /// Leave out progress banners, capability printouts and the pauses after them; prompts, warnings and errors are still shown
+    #[arg(short, long, conflicts_with_all = ["detect_only", "refresh_list", "benchmark_detection"])]
+    pub quiet: bool,
+
+    /// Enable debug logging (to stderr, or to the state directory in TUI modes)
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Write debug logs to this file
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub log_file: Option<PathBuf>,
+
+    /// Additional gamescope arguments
+    #[arg(last = true)]
+    pub extra_args: Vec<String>,
+}
+
+/// Parse a `KEY=VALUE` pair for --env; the value may be empty or contain '='
+pub fn parse_env_var(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{}'", s)),
+    }
+}
+
+/// Parse a non-negative, finite --delay-scale
+pub fn parse_delay_scale(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(scale) if scale.is_finite() && scale >= 0.0 => Ok(scale),
+        _ => Err(format!("expected a non-negative number, got '{}'", s)),
+    }
+}
+
+/// Parse --refresh-rate: whole or fractional Hz (e.g. 60 or 59.94)
+pub fn parse_refresh_rate(s: &str) -> Result<f32, String> {
+    match s.trim().parse::<f32>() {
+        Ok(rate) if rate.is_finite() && rate >= 1.0 => Ok(rate),
+        _ => Err(format!("expected a refresh rate in Hz like 60 or 59.94, got '{}'", s)),
+    }
+}
+
+/// Parse --bpc, which the kernel only knows as 8, 10 or 12 for displays
+pub fn parse_bpc(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(bpc @ (8 | 10 | 12)) => Ok(bpc),
+        _ => Err(format!("expected 8, 10 or 12, got '{}'", s)),
+    }
+}
+
+/// Parse --rotate, which gamescope only does in quarter turns
+pub fn parse_rotation(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(degrees @ (0 | 90 | 180 | 270)) => Ok(degrees),
+        _ => Err(format!("expected 0, 90, 180 or 270, got '{}'", s)),
+    }
+}
+
+/// MCCS input source values for --ddc-input's names
+const DDC_INPUTS: [(&str, u8); 8] = [
+    ("vga1", 0x01), ("vga2", 0x02), ("dvi1", 0x03), ("dvi2", 0x04),
+    ("dp1", 0x0f), ("dp2", 0x10), ("hdmi1", 0x11), ("hdmi2", 0x12),
+];
+
+/// Parse --ddc-input: an input name, or a raw VCP 0x60 value in hex or decimal
+/// for monitors with their own numbering (e.g. USB-C)
+pub fn parse_ddc_input(s: &str) -> Result<u8, String> {
+    let s = s.trim().to_lowercase();
+    if let Some(&(_, value)) = DDC_INPUTS.iter().find(|(name, _)| *name == s) {
+        return Ok(value);
+    }
+    match s.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+    .ok_or_else(|| format!("expected an input like dp1 or hdmi1, or a VCP value like 0x0f, got '{}'", s))
+}
+
+/// Gamescope scaler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ScalingMode {
+    /// Keep the aspect ratio, letterboxing if needed
+    Fit,
+    /// Keep the aspect ratio, cropping to fill the output
+    Fill,
+    /// Stretch to the output, ignoring the aspect ratio
+    Stretch,
+    /// Scale by whole multiples only, for sharp pixels
+    Integer,
+}
+
+/// Gamescope upscaling filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ScalingFilter {
+    Linear,
+    Nearest,
+    /// AMD FidelityFX Super Resolution 1.0
+    Fsr,
+    /// NVIDIA Image Scaling
+    Nis,
+}
+
+/// Color format sent over HDMI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorFormat {
+    /// YCbCr 4:2:0 only when the mode doesn't fit the HDMI link in RGB and
+    /// the display lists it for 4:2:0
+    Auto,
+    Rgb,
+    /// Half the bandwidth of RGB, with reduced color resolution
+    Ycbcr420,
+}
+
+/// What to do once the gamescope session ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AfterLaunch {
+    /// Exit console-mode
+    Exit,
+    /// Launch the same session again
+    Relaunch,
+    /// Go back to the TUI display selector
+    Menu,
+}
+
+/// What to do when --launcher selection is cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnCancel {
+    /// Exit console-mode
+    Abort,
+    /// Use the first display
+    First,
+    /// Ask in the terminal instead
+    Interactive,
+}
+
+/// Ordering applied to the detected display list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortOrder {
+    /// Connector name, internal panels (eDP) first
+    Name,
+    /// Largest native resolution first
+    Resolution,
+    /// Highest refresh rate (from EDID) first
+    Refresh,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_vars_split_at_the_first_equals_sign() {
+        assert_eq!(parse_env_var("DXVK_HUD=fps"), Ok(("DXVK_HUD".to_string(), "fps".to_string())));
+        assert_eq!(parse_env_var("PROTON_ARGS=a=b"), Ok(("PROTON_ARGS".to_string(), "a=b".to_string())));
+        assert_eq!(parse_env_var("EMPTY="), Ok(("EMPTY".to_string(), String::new())));
+        assert!(parse_env_var("NOVALUE").is_err());
+        assert!(parse_env_var("=value").is_err());
+    }
+
+    #[test]
+    fn ddc_inputs_are_names_or_vcp_values() {
+        assert_eq!(parse_ddc_input("HDMI1"), Ok(0x11));
+        assert_eq!(parse_ddc_input("dp1"), Ok(0x0f));
+        assert_eq!(parse_ddc_input("0x1b"), Ok(0x1b));
+        assert_eq!(parse_ddc_input("17"), Ok(17));
+        assert!(parse_ddc_input("usb").is_err());
+        assert!(parse_ddc_input("0x100").is_err());
+    }
+
+    #[test]
+    fn rotation_is_a_quarter_turn() {
+        assert_eq!(parse_rotation("270"), Ok(270));
+        assert_eq!(parse_rotation("0"), Ok(0));
+        assert!(parse_rotation("45").is_err());
+        assert!(parse_rotation("-90").is_err());
+    }
+
+    #[test]
+    fn delay_scale_must_be_non_negative() {
+        assert_eq!(parse_delay_scale("0.5"), Ok(0.5));
+        assert_eq!(parse_delay_scale("0"), Ok(0.0));
+        assert!(parse_delay_scale("-1").is_err());
+        assert!(parse_delay_scale("inf").is_err());
+        assert!(parse_delay_scale("fast").is_err());
+    }
+
+    #[test]
+    fn refresh_rate_may_be_fractional() {
+        assert_eq!(parse_refresh_rate("120"), Ok(120.0));
+        assert_eq!(parse_refresh_rate("59.94"), Ok(59.94));
+        assert!(parse_refresh_rate("0").is_err());
+        assert!(parse_refresh_rate("NaN").is_err());
+        assert!(parse_refresh_rate("fast").is_err());
+    }
+
+    #[test]
+    fn bpc_must_be_a_display_bit_depth() {
+        assert_eq!(parse_bpc("10"), Ok(10));
+        assert!(parse_bpc("16").is_err());
+        assert!(parse_bpc("ten").is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::Args;
+use crate::controller::{ButtonAction, ControllerLayout};
+use crate::theme::{ThemeColors, ThemeName};
+
+/// Configuration loaded from `$XDG_CONFIG_HOME/console-mode/config.toml`
+/// (`~/.config/console-mode/config.toml` by default) or `--config`
+///
+/// ```toml
+/// mangoapp = false
+/// pre_launch = "ledctl on"
+/// post_launch = "ledctl off"
+///
+/// [env]
+/// DXVK_HUD = "fps"
+///
+/// [controller]
+/// layout = "nintendo"
+/// buttons = { BTN_START = "select" }
+///
+/// [theme]
+/// name = "light"
+/// border = "magenta"
+///
+/// [displays.card1-HDMI-A-1]
+/// resolution = "3840x2160"
+/// refresh_rate = 120
+/// force_vrr = true
+/// force_hdr = true
+/// extra_args = ["--fsr-sharpness", "5"]
+///
+/// [profiles.couch]
+/// display = "card1-HDMI-A-1"
+/// resolution = "3840x2160@120"
+/// force_hdr = true
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Profiles keyed by connector name ("card1-HDMI-A-1" or just "HDMI-A-1")
+    pub displays: HashMap<String, DisplayProfile>,
+    /// Profiles picked by name with --profile
+    pub profiles: BTreeMap<String, NamedProfile>,
+    /// Environment variables for the gamescope session, underneath any --env
+    pub env: BTreeMap<String, String>,
+    pub controller: ControllerConfig,
+    pub theme: ThemeConfig,
+    /// `false` is the same as --no-mangoapp
+    pub mangoapp: Option<bool>,
+    /// Shell command run before each launch, underneath --pre-launch
+    pub pre_launch: Option<String>,
+    /// Shell command run after each launch, underneath --post-launch
+    pub post_launch: Option<String>,
+    /// `true` is the same as --pre-launch-required
+    pub pre_launch_required: Option<bool>,
+}
+
+/// TUI controller buttons, underneath --controller-layout
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ControllerConfig {
+    pub layout: Option<ControllerLayout>,
+    /// Actions for individual buttons by evdev name, on top of the layout
+    pub buttons: HashMap<String, ButtonAction>,
+}
+
+/// TUI colors, underneath --theme
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub name: Option<ThemeName>,
+    /// Colors replacing the theme's
+    #[serde(flatten)]
+    pub colors: ThemeColors,
+}
+
+/// Per-display overrides, applied unless the matching CLI flag is given
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct DisplayProfile {
+    pub resolution: Option<String>,
+    pub refresh_rate: Option<f32>,
+    /// `true` forces VRR on, `false` forces it off
+    pub force_vrr: Option<bool>,
+    /// `true` forces HDR on, `false` forces it off
+    pub force_hdr: Option<bool>,
+    /// Additional gamescope arguments, placed before any CLI extra args
+    pub extra_args: Vec<String>,
+}
+
+impl DisplayProfile {
+    /// Merge this profile into the args; explicit CLI flags win
+    pub fn apply(&self, args: &mut Args) {
+        if args.resolution.is_none() {
+            args.resolution = self.resolution.clone();
+        }
+
+        if args.refresh_rate.is_none() {
+            args.refresh_rate = self.refresh_rate;
+        }
+
+        if !args.force_vrr && !args.no_vrr {
+            match self.force_vrr {
+                Some(true) => args.force_vrr = true,
+                Some(false) => args.no_vrr = true,
+                None => {}
+            }
+        }
+
+        if !args.force_hdr && !args.no_hdr {
+            match self.force_hdr {
+                Some(true) => args.force_hdr = true,
+                Some(false) => args.no_hdr = true,
+                None => {}
+            }
+        }
+
+        // Profile args go first so CLI extra args can override them
+        let mut extra_args = self.extra_args.clone();
+        extra_args.append(&mut args.extra_args);
+        args.extra_args = extra_args;
+    }
+}
+
+/// A profile picked with --profile: a display plus the same overrides as
+/// a per-display profile
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct NamedProfile {
+    /// Connector to launch on, like --display
+    pub display: Option<String>,
+    #[serde(flatten)]
+    pub overrides: DisplayProfile,
+}
+
+impl NamedProfile {
+    /// Merge this profile into the args; explicit CLI flags win
+    pub fn apply(&self, args: &mut Args) {
+        if args.display.is_none() {
+            args.display = self.display.clone();
+        }
+        self.overrides.apply(args);
+    }
+}
+
+impl Config {
+    /// Load the config file, falling back to an empty config if none exists.
+    /// An explicit path (from --config) skips discovery and must exist.
+    pub fn load(explicit_path: Option<&Path>) -> Result<Self> {
+        let path = if let Some(path) = explicit_path {
+            if !path.exists() {
+                anyhow::bail!("Config file {} does not exist", path.display());
+            }
+            path.to_path_buf()
+        } else {
+            match config_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Self::default()),
+            }
+        };
+
+        let contents = fs::read_to_string(&path)
+            .context(format!("Failed to read config file {}", path.display()))?;
+
+        toml::from_str(&contents)
+            .context(format!("Failed to parse config file {}", path.display()))
+    }
+
+    /// Put the `[env]` variables ahead of the --env ones, so a variable
+    /// given on the command line wins when the session command is built
+    pub fn apply_env(&self, args: &mut Args) {
+        let mut env: Vec<(String, String)> = self.env.clone().into_iter().collect();
+        env.append(&mut args.env);
+        args.env = env;
+    }
+
+    /// Use the config's launch hooks where --pre-launch/--post-launch weren't given
+    pub fn apply_hooks(&self, args: &mut Args) {
+        if args.pre_launch.is_none() {
+            args.pre_launch = self.pre_launch.clone();
+        }
+        if args.post_launch.is_none() {
+            args.post_launch = self.post_launch.clone();
+        }
+        if self.pre_launch_required == Some(true) {
+            args.pre_launch_required = true;
+        }
+    }
+
+    /// Look up a --profile by name
+    pub fn named_profile(&self, name: &str) -> Result<&NamedProfile> {
+        self.profiles.get(name).with_context(|| {
+            let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            if names.is_empty() {
+                format!("Profile '{}' not found; no [profiles.*] tables in the config", name)
+            } else {
+                format!("Profile '{}' not found (available: {})", name, names.join(", "))
+            }
+        })
+    }
+
+    /// Find the profile for a connector, trying the full name before the
+    /// name without its "cardX-" prefix
+    pub fn profile_for(&self, connector_name: &str) -> Option<&DisplayProfile> {
+        self.displays.get(connector_name).or_else(|| {
+            connector_name
+                .split_once('-')
+                .and_then(|(_, output)| self.displays.get(output))
+        })
+    }
+}
+
+/// `$XDG_CONFIG_HOME/console-mode/config.toml`, defaulting to `~/.config`
+fn config_path() -> Option<PathBuf> {
+    let config_home = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_home.join("console-mode/config.toml"))
+}
@@ -0,0 +1,121 @@
+//! Gamepad button mappings for the TUI
+
+use anyhow::Result;
+use clap::ValueEnum;
+use evdev::Key;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Which face buttons mean confirm and back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ControllerLayout {
+    /// A (south) or X (west) selects, B (east) goes back
+    #[default]
+    Xbox,
+    /// A (east) or X (north) selects, B (south) goes back
+    Nintendo,
+}
+
+/// What a button does in the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ButtonAction {
+    Up,
+    Down,
+    Select,
+    Back,
+    /// Cycle the VRR override (auto, on, off)
+    #[serde(rename = "toggle_vrr")]
+    ToggleVrr,
+    /// Cycle the HDR override (auto, on, off)
+    #[serde(rename = "toggle_hdr")]
+    ToggleHdr,
+}
+
+/// Buttons that can be remapped, by their evdev names
+const BUTTON_NAMES: &[(&str, Key)] = &[
+    ("BTN_SOUTH", Key::BTN_SOUTH),
+    ("BTN_EAST", Key::BTN_EAST),
+    ("BTN_NORTH", Key::BTN_NORTH),
+    ("BTN_WEST", Key::BTN_WEST),
+    ("BTN_TL", Key::BTN_TL),
+    ("BTN_TR", Key::BTN_TR),
+    ("BTN_TL2", Key::BTN_TL2),
+    ("BTN_TR2", Key::BTN_TR2),
+    ("BTN_SELECT", Key::BTN_SELECT),
+    ("BTN_START", Key::BTN_START),
+    ("BTN_MODE", Key::BTN_MODE),
+    ("BTN_THUMBL", Key::BTN_THUMBL),
+    ("BTN_THUMBR", Key::BTN_THUMBR),
+    ("BTN_DPAD_UP", Key::BTN_DPAD_UP),
+    ("BTN_DPAD_DOWN", Key::BTN_DPAD_DOWN),
+    ("BTN_DPAD_LEFT", Key::BTN_DPAD_LEFT),
+    ("BTN_DPAD_RIGHT", Key::BTN_DPAD_RIGHT),
+];
+
+/// Build the button map for a layout, with per-button overrides from the
+/// config (`BTN_NORTH = "back"`) on top
+pub fn button_map(layout: ControllerLayout, overrides: &HashMap<String, ButtonAction>) -> Result<HashMap<Key, ButtonAction>> {
+    let mut map = HashMap::from([
+        (Key::BTN_DPAD_UP, ButtonAction::Up),
+        (Key::BTN_DPAD_DOWN, ButtonAction::Down),
+        (Key::BTN_TL, ButtonAction::ToggleVrr),
+        (Key::BTN_TR, ButtonAction::ToggleHdr),
+    ]);
+    match layout {
+        ControllerLayout::Xbox => map.extend([
+            (Key::BTN_SOUTH, ButtonAction::Select),
+            (Key::BTN_WEST, ButtonAction::Select),
+            (Key::BTN_EAST, ButtonAction::Back),
+        ]),
+        ControllerLayout::Nintendo => map.extend([
+            (Key::BTN_EAST, ButtonAction::Select),
+            (Key::BTN_NORTH, ButtonAction::Select),
+            (Key::BTN_SOUTH, ButtonAction::Back),
+        ]),
+    }
+
+    for (name, &action) in overrides {
+        let Some(&(_, key)) = BUTTON_NAMES.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)) else {
+            let known: Vec<&str> = BUTTON_NAMES.iter().map(|(n, _)| *n).collect();
+            anyhow::bail!("Unknown controller button '{}' (expected one of {})", name, known.join(", "));
+        };
+        map.insert(key, action);
+    }
+
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nintendo_layout_swaps_confirm_and_back() {
+        let xbox = button_map(ControllerLayout::Xbox, &HashMap::new()).unwrap();
+        assert_eq!(xbox[&Key::BTN_SOUTH], ButtonAction::Select);
+        assert_eq!(xbox[&Key::BTN_EAST], ButtonAction::Back);
+
+        let nintendo = button_map(ControllerLayout::Nintendo, &HashMap::new()).unwrap();
+        assert_eq!(nintendo[&Key::BTN_EAST], ButtonAction::Select);
+        assert_eq!(nintendo[&Key::BTN_SOUTH], ButtonAction::Back);
+        assert_eq!(nintendo[&Key::BTN_DPAD_UP], ButtonAction::Up);
+        assert_eq!(nintendo[&Key::BTN_TL], ButtonAction::ToggleVrr);
+    }
+
+    #[test]
+    fn overrides_apply_on_top_of_the_layout() {
+        let overrides = HashMap::from([
+            ("BTN_START".to_string(), ButtonAction::Select),
+            ("btn_west".to_string(), ButtonAction::Back),
+        ]);
+        let map = button_map(ControllerLayout::Xbox, &overrides).unwrap();
+        assert_eq!(map[&Key::BTN_START], ButtonAction::Select);
+        assert_eq!(map[&Key::BTN_WEST], ButtonAction::Back);
+        assert_eq!(map[&Key::BTN_SOUTH], ButtonAction::Select);
+
+        let unknown = HashMap::from([("BTN_Z".to_string(), ButtonAction::Select)]);
+        assert!(button_map(ControllerLayout::Xbox, &unknown).is_err());
+    }
+}
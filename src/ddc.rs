@@ -0,0 +1,95 @@
+//! DDC/CI control of the selected monitor through ddcutil (`ddc` feature):
+//! power it on and switch it to our input, for DisplayPort and DVI monitors
+//! that CEC doesn't reach
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::runner::CommandRunner;
+
+/// MCCS VCP code for the active input
+const VCP_INPUT_SOURCE: &str = "60";
+/// MCCS VCP code for the power mode; 1 is on
+const VCP_POWER_MODE: &str = "d6";
+
+/// The I2C bus of a connector's DDC channel, from its `ddc` link, or the
+/// `i2c-N` directory DisplayPort connectors have instead
+pub fn i2c_bus(connector_path: &Path) -> Option<u32> {
+    let bus_number = |name: &str| name.strip_prefix("i2c-")?.parse().ok();
+
+    if let Ok(target) = fs::read_link(connector_path.join("ddc")) {
+        if let Some(bus) = target.file_name().and_then(|name| bus_number(&name.to_string_lossy())) {
+            return Some(bus);
+        }
+    }
+
+    fs::read_dir(connector_path).ok()?
+        .flatten()
+        .find_map(|entry| bus_number(&entry.file_name().to_string_lossy()))
+}
+
+/// Wake the monitor on `bus` from standby
+pub fn power_on(runner: &dyn CommandRunner, bus: u32) -> Result<()> {
+    setvcp(runner, bus, VCP_POWER_MODE, 0x01)
+}
+
+/// Switch the monitor on `bus` to an input (a VCP 0x60 value, e.g. 0x0f for DisplayPort 1)
+pub fn switch_input(runner: &dyn CommandRunner, bus: u32, input: u8) -> Result<()> {
+    setvcp(runner, bus, VCP_INPUT_SOURCE, input)
+}
+
+fn setvcp(runner: &dyn CommandRunner, bus: u32, code: &str, value: u8) -> Result<()> {
+    let mut cmd = Command::new("ddcutil");
+    cmd.args(["--bus", &bus.to_string(), "setvcp", code, &format!("0x{:02x}", value)]);
+    let output = runner.output(&mut cmd, &[])
+        .context("Failed to run ddcutil (is it installed?)")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "ddcutil setvcp {} failed on bus {}: {}",
+            code, bus, String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::MockRunner;
+
+    #[test]
+    fn bus_comes_from_the_ddc_link_or_an_i2c_directory() {
+        let root = std::env::temp_dir().join(format!("console-mode-ddc-{}", std::process::id()));
+        let hdmi = root.join("card1-HDMI-A-1");
+        let dp = root.join("card1-DP-1");
+        fs::create_dir_all(&hdmi).unwrap();
+        fs::create_dir_all(dp.join("i2c-7")).unwrap();
+        std::os::unix::fs::symlink("../../i2c-4", hdmi.join("ddc")).unwrap();
+
+        assert_eq!(i2c_bus(&hdmi), Some(4));
+        assert_eq!(i2c_bus(&dp), Some(7));
+        assert_eq!(i2c_bus(&root), None);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn commands_set_the_vcp_features() {
+        let runner = MockRunner::new();
+        power_on(&runner, 4).unwrap();
+        switch_input(&runner, 4, 0x0f).unwrap();
+
+        assert_eq!(runner.calls(), [
+            ["ddcutil", "--bus", "4", "setvcp", "d6", "0x01"],
+            ["ddcutil", "--bus", "4", "setvcp", "60", "0x0f"],
+        ]);
+    }
+
+    #[test]
+    fn failures_are_errors() {
+        let runner = MockRunner::new().with_output("ddcutil", 1, "");
+        assert!(power_on(&runner, 4).is_err());
+    }
+}
@@ -0,0 +1,725 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::SortOrder;
+use crate::debug_log;
+use crate::edid::{edid_max_refresh, edid_monitor_name, edid_preferred_mode, edid_serial};
+use crate::error::{Error, Result};
+
+/// Where the kernel exposes DRM connectors
+pub const DRM_PATH: &str = "/sys/class/drm";
+
+/// Resolution given to a connected display that lists no modes
+pub const PLACEHOLDER_MODE: (u32, u32) = (1920, 1080);
+
+#[derive(Debug, Clone)]
+pub struct DisplayInfo {
+    pub connector_name: String,
+    pub connector_path: PathBuf,
+    pub resolution: String,
+    pub width: u32,
+    pub height: u32,
+    /// All modes from the DRM `modes` file, in kernel order without duplicates
+    pub available_modes: Vec<(u32, u32)>,
+    /// Another GPU has a connected output of the same name (e.g. two HDMI-A-1)
+    pub output_ambiguous: bool,
+    /// PCI "vendor:device" ID of the GPU driving this connector
+    pub gpu_id: Option<String>,
+    /// Connected, but with a missing or empty `modes` file (bad cable or
+    /// port); the resolution is [`PLACEHOLDER_MODE`]
+    pub modes_unavailable: bool,
+    /// Monitor name from the EDID, e.g. "LG TV SSCR2"
+    pub model: Option<String>,
+    /// Serial number string from the EDID
+    pub serial: Option<String>,
+    /// The panel's native mode: the EDID's preferred timing if the kernel
+    /// lists it, otherwise the kernel's first mode. This is the default
+    /// resolution.
+    pub native_mode: Option<(u32, u32)>,
+}
+
+impl DisplayInfo {
+    /// Whether the EDID monitor name or serial contains `text`, ignoring case
+    pub fn model_matches(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        [&self.model, &self.serial]
+            .into_iter()
+            .flatten()
+            .any(|value| value.to_lowercase().contains(&text))
+    }
+
+    /// Connector name without its "cardX-" prefix, as gamescope knows it
+    pub fn output_name(&self) -> &str {
+        self.connector_name
+            .split_once('-')
+            .map_or(self.connector_name.as_str(), |(_, name)| name)
+    }
+}
+
+/// Connector types that don't drive a physical output
+const VIRTUAL_CONNECTORS: [&str; 2] = ["Writeback", "Virtual"];
+
+/// Detect connected displays under a DRM sysfs root (normally [`DRM_PATH`]).
+/// Writeback and virtual connectors are left out unless `include_virtual`.
+pub fn detect_displays(drm_path: &Path, order: SortOrder, include_virtual: bool) -> Result<Vec<DisplayInfo>> {
+    let mut found = Vec::new();
+    let unreadable = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| Error::DisplayDetection { path, source }
+    };
+
+    for entry in fs::read_dir(drm_path).map_err(unreadable(drm_path))? {
+        let entry = entry.map_err(unreadable(drm_path))?;
+        let path = entry.path();
+
+        // Look for card*-* directories (e.g., card1-HDMI-A-1)
+        let dir_name = entry.file_name();
+        let dir_name_str = dir_name.to_string_lossy();
+
+        if !dir_name_str.starts_with("card") || !dir_name_str.contains('-') {
+            continue;
+        }
+
+        let connector_type = dir_name_str.split_once('-').map_or("", |(_, output)| output);
+        if !include_virtual && VIRTUAL_CONNECTORS.iter().any(|kind| connector_type.starts_with(kind)) {
+            debug_log(&format!("{}: skipping virtual connector (use --include-virtual to list it)", dir_name_str));
+            continue;
+        }
+
+        let status_file = path.join("status");
+        if !status_file.exists() {
+            continue;
+        }
+
+        let status = fs::read_to_string(&status_file)
+            .map_err(unreadable(&status_file))?
+            .trim()
+            .to_string();
+
+        if status == "connected" {
+            let modes = fs::read_to_string(path.join("modes")).unwrap_or_default();
+            let available_modes = parse_modes(&modes);
+
+            let modes_unavailable = available_modes.is_empty();
+            let edid = fs::read(path.join("edid")).unwrap_or_default();
+            let native_mode = native_mode(&dir_name_str, &available_modes, &edid);
+            let (width, height) = native_mode.unwrap_or(PLACEHOLDER_MODE);
+            let display = DisplayInfo {
+                connector_name: dir_name_str.to_string(),
+                connector_path: path.clone(),
+                resolution: format!("{}x{}", width, height),
+                width,
+                height,
+                available_modes,
+                output_ambiguous: false,
+                gpu_id: read_gpu_id(drm_path, &dir_name_str),
+                modes_unavailable,
+                model: edid_monitor_name(&edid),
+                serial: edid_serial(&edid),
+                native_mode,
+            };
+            if modes_unavailable {
+                debug_log(&format!("{}: connected but no modes, assuming {}", display.connector_name, display.resolution));
+            } else {
+                debug_log(&format!("{}: {} modes available: {:?}",
+                    display.connector_name, display.available_modes.len(), display.available_modes));
+            }
+
+            let identity = connector_identity(&path, &dir_name_str);
+            found.push((display, identity));
+        }
+    }
+
+    let mut displays = dedupe_connectors(found);
+    mark_ambiguous_outputs(&mut displays);
+
+    // read_dir order is filesystem-dependent, so always sort for a stable list
+    sort_displays(&mut displays, order);
+
+    Ok(displays)
+}
+
+/// The EDID's preferred timing, which is the panel's real native mode, as
+/// long as the kernel can drive it; the kernel usually lists it first, but
+/// not always
+fn native_mode(connector_name: &str, available_modes: &[(u32, u32)], edid: &[u8]) -> Option<(u32, u32)> {
+    let first = available_modes.first().copied();
+    let Some(preferred) = edid_preferred_mode(edid) else {
+        return first;
+    };
+
+    if !available_modes.contains(&preferred) {
+        debug_log(&format!("{}: EDID preferred mode {}x{} isn't in the kernel's modes, using {:?}",
+            connector_name, preferred.0, preferred.1, first));
+        return first;
+    }
+    if let Some((width, height)) = first.filter(|&mode| mode != preferred) {
+        debug_log(&format!("{}: EDID preferred mode is {}x{}, kernel lists {}x{} first; using the EDID's",
+            connector_name, preferred.0, preferred.1, width, height));
+    }
+    Some(preferred)
+}
+
+/// What identifies a connector to the kernel: the card's connector ID if
+/// sysfs has one, otherwise the device directory its entry points to
+fn connector_identity(path: &Path, connector_name: &str) -> PathBuf {
+    let card = connector_name.split_once('-').map_or(connector_name, |(card, _)| card);
+    match fs::read_to_string(path.join("connector_id")) {
+        Ok(id) => Path::new(card).join(id.trim()),
+        Err(_) => fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()),
+    }
+}
+
+/// Drop entries that are the same connector as one already found (same
+/// connector ID on the same card, or the same sysfs device), which some
+/// drivers list twice. The first by name is kept. Two monitors of the same
+/// model can have identical EDIDs, so the EDID isn't compared.
+fn dedupe_connectors(mut found: Vec<(DisplayInfo, PathBuf)>) -> Vec<DisplayInfo> {
+    found.sort_by(|(a, _), (b, _)| a.connector_name.cmp(&b.connector_name));
+
+    let mut kept: Vec<(DisplayInfo, PathBuf)> = Vec::new();
+    for (display, identity) in found {
+        let duplicate_of = kept.iter()
+            .find(|(_, other_identity)| *other_identity == identity)
+            .map(|(other, _)| other.connector_name.clone());
+        match duplicate_of {
+            Some(other) => debug_log(&format!("{}: same connector as {}, skipping duplicate", display.connector_name, other)),
+            None => kept.push((display, identity)),
+        }
+    }
+
+    kept.into_iter().map(|(display, _)| display).collect()
+}
+
+/// Flag displays whose output name is shared with a display on another GPU,
+/// which happens on hybrid systems with outputs on both card0 and card1
+fn mark_ambiguous_outputs(displays: &mut [DisplayInfo]) {
+    let names: Vec<String> = displays.iter().map(|d| d.output_name().to_string()).collect();
+    for (i, display) in displays.iter_mut().enumerate() {
+        let shared = names.iter().enumerate().any(|(j, name)| j != i && *name == names[i]);
+        if shared {
+            debug_log(&format!("{}: output name {} is shared with another GPU",
+                display.connector_name, names[i]));
+            display.output_ambiguous = true;
+        }
+    }
+}
+
+/// A GPU chosen with --gpu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuSelection {
+    /// DRM card name, e.g. "card1"
+    pub card: String,
+    /// PCI address in Mesa's DRI_PRIME form, e.g. "pci-0000_03_00_0"
+    pub dri_prime: Option<String>,
+}
+
+/// Resolve a --gpu value ("card1", "/dev/dri/card1" or "/dev/dri/renderD128")
+/// to its card under a DRM sysfs root (normally [`DRM_PATH`])
+pub fn resolve_gpu(drm_path: &Path, gpu: &str) -> Result<GpuSelection> {
+    let name = Path::new(gpu).file_name().and_then(|name| name.to_str()).unwrap_or(gpu);
+    let is_card = name.starts_with("card") && !name.contains('-');
+    if !is_card && !name.starts_with("renderD") {
+        return Err(Error::InvalidGpu(gpu.to_string()));
+    }
+
+    let device = fs::canonicalize(drm_path.join(name).join("device"))
+        .map_err(|source| Error::GpuNotFound { gpu: gpu.to_string(), drm_path: drm_path.to_path_buf(), source })?;
+
+    // A render node belongs to the card on the same device
+    let card = if is_card {
+        name.to_string()
+    } else {
+        fs::read_dir(drm_path)
+            .map_err(|source| Error::DisplayDetection { path: drm_path.to_path_buf(), source })?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|entry| entry.starts_with("card") && !entry.contains('-'))
+            .find(|card| fs::canonicalize(drm_path.join(card).join("device")).is_ok_and(|d| d == device))
+            .ok_or_else(|| Error::NoCardForRenderNode(gpu.to_string()))?
+    };
+
+    // The device directory is named after its PCI address ("0000:03:00.0")
+    let dri_prime = device.file_name()
+        .and_then(|slot| slot.to_str())
+        .filter(|slot| slot.contains(':'))
+        .map(|slot| format!("pci-{}", slot.replace([':', '.'], "_")));
+
+    Ok(GpuSelection { card, dri_prime })
+}
+
+/// Read the PCI vendor and device IDs of a card ("1002:73ff")
+fn read_gpu_id(drm_path: &Path, connector_name: &str) -> Option<String> {
+    let (card, _) = connector_name.split_once('-')?;
+    let device_path = drm_path.join(card).join("device");
+    let read_id = |file: &str| -> Option<String> {
+        let id = fs::read_to_string(device_path.join(file)).ok()?;
+        Some(id.trim().trim_start_matches("0x").to_string())
+    };
+    Some(format!("{}:{}", read_id("vendor")?, read_id("device")?))
+}
+
+/// Sort displays so that list positions are stable across boots
+pub fn sort_displays(displays: &mut [DisplayInfo], order: SortOrder) {
+    match order {
+        SortOrder::Name => {
+            displays.sort_by_key(|d| (!is_internal_panel(d), d.connector_name.clone()));
+        }
+        SortOrder::Resolution => {
+            displays.sort_by(|a, b| {
+                let a_pixels = u64::from(a.width) * u64::from(a.height);
+                let b_pixels = u64::from(b.width) * u64::from(b.height);
+                b_pixels.cmp(&a_pixels).then_with(|| a.connector_name.cmp(&b.connector_name))
+            });
+        }
+        SortOrder::Refresh => {
+            displays.sort_by_cached_key(|d| {
+                let refresh = fs::read(d.connector_path.join("edid"))
+                    .ok()
+                    .and_then(|edid| edid_max_refresh(&edid))
+                    .unwrap_or(0);
+                (std::cmp::Reverse(refresh), d.connector_name.clone())
+            });
+        }
+    }
+}
+
+/// Check if the display is a built-in panel (eDP, LVDS or DSI)
+pub fn is_internal_panel(display: &DisplayInfo) -> bool {
+    let output = display.output_name();
+    ["eDP", "LVDS", "DSI"].iter().any(|prefix| output.starts_with(prefix))
+}
+
+/// Parse every valid `WIDTHxHEIGHT` line of a DRM `modes` file
+///
+/// Duplicates (the kernel lists one line per timing) and lines that don't
+/// parse, such as interlaced `1920x1080i` modes, are skipped.
+pub fn parse_modes(modes: &str) -> Vec<(u32, u32)> {
+    let mut available_modes = Vec::new();
+    for line in modes.lines() {
+        if let Ok(mode) = parse_resolution(line) {
+            if !available_modes.contains(&mode) {
+                available_modes.push(mode);
+            }
+        }
+    }
+    available_modes
+}
+
+/// The supported mode nearest to the requested one, by distance in width and height
+pub fn closest_mode(modes: &[(u32, u32)], (width, height): (u32, u32)) -> Option<(u32, u32)> {
+    modes.iter().copied().min_by_key(|&(w, h)| {
+        let dw = i64::from(w) - i64::from(width);
+        let dh = i64::from(h) - i64::from(height);
+        dw * dw + dh * dh
+    })
+}
+
+/// Connector types the kernel (and so sysfs and gamescope) names differently
+/// from how people tend to write them. The amdgpu/radeon X11 drivers'
+/// "DisplayPort-N" isn't one of them: those count from 0 and the kernel from
+/// 1, so it would pick the wrong monitor.
+const CONNECTOR_TYPE_ALIASES: [(&str, &str); 1] = [
+    ("HDMI", "HDMI-A"),
+];
+
+/// The kernel's connector type names, for fixing up the case of e.g. "dp-1"
+const CONNECTOR_TYPES: [&str; 20] = [
+    "VGA", "DVI-I", "DVI-D", "DVI-A", "Composite", "SVIDEO", "LVDS", "Component", "DIN", "DP",
+    "HDMI-A", "HDMI-B", "TV", "eDP", "Virtual", "DSI", "DPI", "Writeback", "SPI", "USB",
+];
+
+/// A connector name in the kernel's form that sysfs uses: "hdmi-1" becomes
+/// "HDMI-A-1" and "dp-1" "DP-1". A "cardX-" prefix is kept. The index isn't
+/// touched.
+pub fn normalize_connector(name: &str) -> String {
+    let name = name.trim();
+    let is_card = |card: &str| card.strip_prefix("card").is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+    let (card, output) = match name.split_once('-') {
+        Some((card, output)) if is_card(card) => (Some(card), output),
+        _ => (None, name),
+    };
+
+    let output = match output.rsplit_once('-') {
+        Some((kind, index)) if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) => {
+            let kind = CONNECTOR_TYPE_ALIASES.iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(kind))
+                .map(|&(_, kernel)| kernel)
+                .or_else(|| CONNECTOR_TYPES.iter().copied().find(|kernel| kernel.eq_ignore_ascii_case(kind)))
+                .unwrap_or(kind);
+            format!("{}-{}", kind, index)
+        }
+        _ => output.to_string(),
+    };
+
+    match card {
+        Some(card) => format!("{}-{}", card, output),
+        None => output,
+    }
+}
+
+/// The display with this connector name (see [`normalize_connector`]; the
+/// "cardX-" prefix may be left out), or else the only one whose EDID
+/// monitor name or serial contains `name` (connector names change when the
+/// cable moves to another port, the monitor name doesn't)
+pub fn find_display<'a>(displays: &'a [DisplayInfo], name: &str) -> Result<&'a DisplayInfo> {
+    if let Some(display) = displays.iter().find(|d| d.connector_name == name) {
+        return Ok(display);
+    }
+
+    let wanted = normalize_connector(name);
+    let by_connector: Vec<&DisplayInfo> = displays.iter()
+        .filter(|d| normalize_connector(&d.connector_name) == wanted || normalize_connector(d.output_name()) == wanted)
+        .collect();
+    let matches = if by_connector.is_empty() {
+        displays.iter().filter(|d| d.model_matches(name)).collect()
+    } else {
+        by_connector
+    };
+    match matches[..] {
+        [display] => Ok(display),
+        [] => Err(Error::DisplayNotFound(name.to_string())),
+        _ => Err(Error::AmbiguousDisplay {
+            name: name.to_string(),
+            connectors: matches.iter().map(|d| d.connector_name.clone()).collect(),
+        }),
+    }
+}
+
+/// The largest mode every display supports, for driving them all at once
+pub fn common_mode(displays: &[DisplayInfo]) -> Option<(u32, u32)> {
+    let (first, rest) = displays.split_first()?;
+    first.available_modes.iter().copied()
+        .filter(|mode| rest.iter().all(|d| d.available_modes.contains(mode)))
+        .max_by_key(|&(w, h)| u64::from(w) * u64::from(h))
+}
+
+/// Parse `WIDTHxHEIGHT` with an optional `@REFRESH` suffix, e.g. "2560x1440@120".
+/// Fractional rates like 59.94 are kept; see `gamescope::whole_hz`.
+pub fn parse_mode(mode: &str) -> Result<(u32, u32, Option<f32>)> {
+    let Some((res, rate)) = mode.trim().split_once('@') else {
+        let (width, height) = parse_resolution(mode)?;
+        return Ok((width, height, None));
+    };
+
+    let (width, height) = parse_resolution(res)?;
+    let refresh = rate.parse::<f32>()
+        .ok()
+        .filter(|rate| rate.is_finite() && *rate >= 1.0)
+        .ok_or_else(|| Error::InvalidRefreshRate(mode.to_string()))?;
+
+    Ok((width, height, Some(refresh)))
+}
+
+pub fn parse_resolution(res: &str) -> Result<(u32, u32)> {
+    let invalid = || Error::InvalidResolution(res.to_string());
+    let (width, height) = res.trim().split_once('x').ok_or_else(invalid)?;
+    let width = width.parse::<u32>().map_err(|_| invalid())?;
+    let height = height.parse::<u32>().map_err(|_| invalid())?;
+
+    Ok((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty stand-in for /sys/class/drm under the temp dir
+    fn fake_drm_root(test_name: &str) -> PathBuf {
+        let root = std::env::temp_dir()
+            .join(format!("console-mode-{}-{}", test_name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn add_connector(root: &Path, name: &str, status: &str, modes: &str) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("status"), format!("{}\n", status)).unwrap();
+        fs::write(dir.join("modes"), modes).unwrap();
+    }
+
+    fn names(displays: &[DisplayInfo]) -> Vec<&str> {
+        displays.iter().map(|d| d.connector_name.as_str()).collect()
+    }
+
+    #[test]
+    fn detects_connected_displays_with_all_modes() {
+        let root = fake_drm_root("detect");
+        add_connector(&root, "card1-HDMI-A-1", "connected", "3840x2160\n1920x1080\n1920x1080\n1920x1080i\n");
+        add_connector(&root, "card1-DP-1", "disconnected", "");
+        // The card directory itself isn't a connector
+        fs::create_dir_all(root.join("card1")).unwrap();
+
+        let displays = detect_displays(&root, SortOrder::Name, false).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(names(&displays), ["card1-HDMI-A-1"]);
+        assert_eq!(displays[0].resolution, "3840x2160");
+        assert_eq!((displays[0].width, displays[0].height), (3840, 2160));
+        assert_eq!(displays[0].available_modes, [(3840, 2160), (1920, 1080)]);
+        assert!(!displays[0].modes_unavailable);
+    }
+
+    #[test]
+    fn skips_virtual_and_duplicate_connectors() {
+        let root = fake_drm_root("dedupe");
+        add_connector(&root, "card1-HDMI-A-1", "connected", "3840x2160\n");
+        add_connector(&root, "card1-HDMI-A-2", "connected", "3840x2160\n");
+        add_connector(&root, "card1-DP-1", "connected", "2560x1440\n");
+        add_connector(&root, "card1-DP-2", "connected", "2560x1440\n");
+        add_connector(&root, "card1-Writeback-1", "connected", "");
+        add_connector(&root, "card2-Virtual-1", "connected", "1280x800\n");
+        add_connector(&root, "card2-HDMI-A-1", "connected", "3840x2160\n");
+        add_connector(&root, "card2-DP-1", "connected", "2560x1440\n");
+        // Two TVs of the same model have the same EDID but are two outputs
+        for name in ["card1-HDMI-A-1", "card1-HDMI-A-2", "card2-HDMI-A-1"] {
+            fs::write(root.join(name).join("edid"), [0x00, 0xff, 0xff, 0x00]).unwrap();
+        }
+        // The same connector listed twice, and the same ID on another card
+        for (name, id) in [("card1-DP-1", "91"), ("card1-DP-2", "91"), ("card2-DP-1", "91")] {
+            fs::write(root.join(name).join("connector_id"), id).unwrap();
+        }
+        // A second entry pointing at the same sysfs device
+        std::os::unix::fs::symlink(root.join("card1-HDMI-A-1"), root.join("card1-HDMI-A-3")).unwrap();
+
+        let displays = detect_displays(&root, SortOrder::Name, false).unwrap();
+        let with_virtual = detect_displays(&root, SortOrder::Name, true).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(names(&displays), ["card1-DP-1", "card1-HDMI-A-1", "card1-HDMI-A-2", "card2-DP-1", "card2-HDMI-A-1"]);
+        assert_eq!(names(&with_virtual), [
+            "card1-DP-1", "card1-HDMI-A-1", "card1-HDMI-A-2", "card1-Writeback-1",
+            "card2-DP-1", "card2-HDMI-A-1", "card2-Virtual-1",
+        ]);
+    }
+
+    #[test]
+    fn connected_display_without_modes_gets_placeholder() {
+        let root = fake_drm_root("no-modes");
+        add_connector(&root, "card1-HDMI-A-1", "connected", "");
+        add_connector(&root, "card1-DP-1", "connected", "2560x1440\n");
+        fs::remove_file(root.join("card1-DP-1/modes")).unwrap();
+
+        let displays = detect_displays(&root, SortOrder::Name, false).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(names(&displays), ["card1-DP-1", "card1-HDMI-A-1"]);
+        for display in &displays {
+            assert!(display.modes_unavailable);
+            assert_eq!(display.resolution, "1920x1080");
+            assert!(display.available_modes.is_empty());
+        }
+    }
+
+    #[test]
+    fn name_order_puts_internal_panel_first() {
+        let root = fake_drm_root("sort-name");
+        add_connector(&root, "card1-HDMI-A-1", "connected", "1920x1080\n");
+        add_connector(&root, "card1-eDP-1", "connected", "2560x1600\n");
+        add_connector(&root, "card1-DP-1", "connected", "2560x1440\n");
+
+        let displays = detect_displays(&root, SortOrder::Name, false).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(names(&displays), ["card1-eDP-1", "card1-DP-1", "card1-HDMI-A-1"]);
+    }
+
+    #[test]
+    fn resolution_order_puts_largest_first() {
+        let root = fake_drm_root("sort-resolution");
+        add_connector(&root, "card1-HDMI-A-1", "connected", "1920x1080\n");
+        add_connector(&root, "card1-DP-2", "connected", "3840x2160\n");
+        add_connector(&root, "card1-DP-1", "connected", "1920x1080\n");
+
+        let displays = detect_displays(&root, SortOrder::Resolution, false).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(names(&displays), ["card1-DP-2", "card1-DP-1", "card1-HDMI-A-1"]);
+    }
+
+    #[test]
+    fn shared_output_names_across_gpus_are_ambiguous() {
+        let root = fake_drm_root("multi-gpu");
+        add_connector(&root, "card0-HDMI-A-1", "connected", "1920x1080\n");
+        add_connector(&root, "card1-HDMI-A-1", "connected", "3840x2160\n");
+        add_connector(&root, "card1-DP-1", "connected", "2560x1440\n");
+        fs::create_dir_all(root.join("card1/device")).unwrap();
+        fs::write(root.join("card1/device/vendor"), "0x1002\n").unwrap();
+        fs::write(root.join("card1/device/device"), "0x73ff\n").unwrap();
+
+        let displays = detect_displays(&root, SortOrder::Name, false).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let ambiguous: Vec<_> = displays.iter().map(|d| (d.connector_name.as_str(), d.output_ambiguous)).collect();
+        assert_eq!(ambiguous, [("card0-HDMI-A-1", true), ("card1-DP-1", false), ("card1-HDMI-A-1", true)]);
+        assert_eq!(displays[0].gpu_id, None);
+        assert_eq!(displays[2].gpu_id.as_deref(), Some("1002:73ff"));
+    }
+
+    #[test]
+    fn gpu_resolves_from_card_or_render_node() {
+        let root = fake_drm_root("gpu");
+        let pci_device = root.join("devices/0000:03:00.0");
+        fs::create_dir_all(&pci_device).unwrap();
+        for node in ["card1", "renderD129"] {
+            fs::create_dir_all(root.join(node)).unwrap();
+            std::os::unix::fs::symlink(&pci_device, root.join(node).join("device")).unwrap();
+        }
+        fs::create_dir_all(root.join("card0/device")).unwrap();
+
+        let card = resolve_gpu(&root, "card1").unwrap();
+        let render = resolve_gpu(&root, "/dev/dri/renderD129").unwrap();
+        let missing = resolve_gpu(&root, "card2");
+        let invalid = resolve_gpu(&root, "card1-HDMI-A-1");
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(card, GpuSelection { card: "card1".to_string(), dri_prime: Some("pci-0000_03_00_0".to_string()) });
+        assert_eq!(render, card);
+        assert!(matches!(missing, Err(Error::GpuNotFound { .. })));
+        assert!(matches!(invalid, Err(Error::InvalidGpu(_))));
+    }
+
+    #[test]
+    fn unknown_display_name_is_display_not_found() {
+        let root = fake_drm_root("find");
+        add_connector(&root, "card1-HDMI-A-1", "connected", "1920x1080\n");
+        let displays = detect_displays(&root, SortOrder::Name, false).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(find_display(&displays, "card1-HDMI-A-1").unwrap().resolution, "1920x1080");
+        assert!(matches!(find_display(&displays, "card1-DP-1"), Err(Error::DisplayNotFound(name)) if name == "card1-DP-1"));
+        assert!(matches!(detect_displays(&root, SortOrder::Name, false), Err(Error::DisplayDetection { .. })));
+    }
+
+    /// A base EDID block with just a monitor name descriptor
+    fn edid_named(name: &str) -> Vec<u8> {
+        let mut edid = vec![0; 128];
+        edid[72..77].copy_from_slice(&[0, 0, 0, 0xfc, 0]);
+        let text = format!("{}\n{}", name, " ".repeat(12 - name.len()));
+        edid[77..90].copy_from_slice(text.as_bytes());
+        edid
+    }
+
+    #[test]
+    fn display_matches_by_edid_monitor_name() {
+        let root = fake_drm_root("model");
+        add_connector(&root, "card1-HDMI-A-1", "connected", "3840x2160\n");
+        add_connector(&root, "card1-DP-1", "connected", "2560x1440\n");
+        fs::write(root.join("card1-HDMI-A-1/edid"), edid_named("LG TV SSCR2")).unwrap();
+        fs::write(root.join("card1-DP-1/edid"), edid_named("DELL U2720Q")).unwrap();
+        let displays = detect_displays(&root, SortOrder::Name, false).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(displays[1].model.as_deref(), Some("LG TV SSCR2"));
+        assert_eq!(find_display(&displays, "lg tv").unwrap().connector_name, "card1-HDMI-A-1");
+        assert_eq!(find_display(&displays, "U2720Q").unwrap().connector_name, "card1-DP-1");
+        assert!(matches!(find_display(&displays, "L"), Err(Error::AmbiguousDisplay { .. })));
+    }
+
+    #[test]
+    fn connector_names_normalize_to_the_kernels() {
+        assert_eq!(normalize_connector("card1-dp-1"), "card1-DP-1");
+        // X11's 0-based name would be off by one, so it's left alone
+        assert_eq!(normalize_connector("DisplayPort-1"), "DisplayPort-1");
+        assert_eq!(normalize_connector("HDMI-1"), "HDMI-A-1");
+        assert_eq!(normalize_connector("hdmi-a-2"), "HDMI-A-2");
+        assert_eq!(normalize_connector("edp-1"), "eDP-1");
+        assert_eq!(normalize_connector("card0-DP-3"), "card0-DP-3");
+        assert_eq!(normalize_connector("LG TV"), "LG TV");
+    }
+
+    #[test]
+    fn display_matches_connector_aliases() {
+        let root = fake_drm_root("aliases");
+        add_connector(&root, "card1-HDMI-A-1", "connected", "3840x2160\n");
+        add_connector(&root, "card1-DP-1", "connected", "2560x1440\n");
+        add_connector(&root, "card2-DP-1", "connected", "1920x1080\n");
+        let displays = detect_displays(&root, SortOrder::Name, false).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(find_display(&displays, "HDMI-1").unwrap().connector_name, "card1-HDMI-A-1");
+        assert_eq!(find_display(&displays, "card2-dp-1").unwrap().connector_name, "card2-DP-1");
+        assert!(matches!(find_display(&displays, "dp-1"), Err(Error::AmbiguousDisplay { .. })));
+        assert!(matches!(find_display(&displays, "DisplayPort-0"), Err(Error::DisplayNotFound(_))));
+    }
+
+    /// A base EDID block whose preferred timing is a 60 Hz mode of this size
+    fn edid_preferring(width: u32, height: u32) -> Vec<u8> {
+        let mut edid = vec![0; 128];
+        let (h_blank, v_blank) = (280u32, 45u32);
+        let pixel_clock = (width + h_blank) * (height + v_blank) * 60 / 10_000;
+        edid[54..56].copy_from_slice(&(pixel_clock as u16).to_le_bytes());
+        edid[56] = width as u8;
+        edid[57] = h_blank as u8;
+        edid[58] = ((width >> 8) << 4 | (h_blank >> 8)) as u8;
+        edid[59] = height as u8;
+        edid[60] = v_blank as u8;
+        edid[61] = ((height >> 8) << 4 | (v_blank >> 8)) as u8;
+        edid
+    }
+
+    #[test]
+    fn defaults_to_the_edid_preferred_mode() {
+        let root = fake_drm_root("native");
+        // The kernel lists a 4K mode first for a 1440p panel that accepts 4K input
+        add_connector(&root, "card1-DP-1", "connected", "3840x2160\n2560x1440\n1920x1080\n");
+        add_connector(&root, "card1-HDMI-A-1", "connected", "1920x1080\n1280x720\n");
+        fs::write(root.join("card1-DP-1/edid"), edid_preferring(2560, 1440)).unwrap();
+        // Not something the kernel can drive, so ignored
+        fs::write(root.join("card1-HDMI-A-1/edid"), edid_preferring(3840, 2160)).unwrap();
+        let displays = detect_displays(&root, SortOrder::Name, false).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(displays[0].native_mode, Some((2560, 1440)));
+        assert_eq!(displays[0].resolution, "2560x1440");
+        assert_eq!(displays[1].native_mode, Some((1920, 1080)));
+    }
+
+    #[test]
+    fn closest_mode_picks_nearest_supported_resolution() {
+        let modes = [(1920, 1080), (1680, 1050), (1280, 720)];
+        assert_eq!(closest_mode(&modes, (3840, 2160)), Some((1920, 1080)));
+        assert_eq!(closest_mode(&modes, (1600, 900)), Some((1680, 1050)));
+        assert_eq!(closest_mode(&modes, (1366, 768)), Some((1280, 720)));
+        assert_eq!(closest_mode(&[], (1920, 1080)), None);
+    }
+
+    #[test]
+    fn common_mode_is_largest_mode_all_displays_share() {
+        let root = fake_drm_root("common-mode");
+        add_connector(&root, "card1-DP-1", "connected", "3840x2160\n2560x1440\n1920x1080\n1280x720\n");
+        add_connector(&root, "card1-HDMI-A-1", "connected", "1920x1080\n1280x720\n");
+        add_connector(&root, "card1-HDMI-A-2", "connected", "2560x1440\n1920x1080\n");
+
+        let displays = detect_displays(&root, SortOrder::Name, false).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(common_mode(&displays), Some((1920, 1080)));
+        assert_eq!(common_mode(&displays[..1]), Some((3840, 2160)));
+        assert_eq!(common_mode(&[]), None);
+    }
+
+    #[test]
+    fn parse_mode_takes_an_optional_refresh_rate() {
+        assert_eq!(parse_mode("2560x1440@120").unwrap(), (2560, 1440, Some(120.0)));
+        assert_eq!(parse_mode("1920x1080@59.94").unwrap(), (1920, 1080, Some(59.94)));
+        assert_eq!(parse_mode("1920x1080").unwrap(), (1920, 1080, None));
+        assert!(parse_mode("1920x1080@").is_err());
+        assert!(parse_mode("1920x1080@fast").is_err());
+        assert!(parse_mode("1920x1080@0").is_err());
+        assert!(parse_mode("@60").is_err());
+    }
+
+    #[test]
+    fn parse_resolution_accepts_only_width_x_height() {
+        assert_eq!(parse_resolution("2560x1440").unwrap(), (2560, 1440));
+        assert_eq!(parse_resolution(" 1920x1080\n").unwrap(), (1920, 1080));
+        assert!(parse_resolution("1920x1080i").is_err());
+        assert!(parse_resolution("1920").is_err());
+        assert!(parse_resolution("axb").is_err());
+        assert!(matches!(parse_resolution("1920x"), Err(Error::InvalidResolution(res)) if res == "1920x"));
+        assert!(matches!(parse_mode("1920x1080@fast"), Err(Error::InvalidRefreshRate(_))));
+    }
+}
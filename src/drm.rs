@@ -0,0 +1,355 @@
+//! Connector properties read and set through DRM ioctls, since sysfs doesn't expose them
+
+use anyhow::{Context, Result};
+use std::fs::{self, File};
+use std::io;
+use std::os::fd::{AsRawFd, RawFd};
+use std::path::{Path, PathBuf};
+
+use crate::debug_log;
+use crate::display::{DisplayInfo, DRM_PATH};
+
+/// HDR state of a connector as the kernel sees it
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HdrState {
+    /// The driver can send HDR metadata on this connector
+    pub metadata_supported: bool,
+    /// HDR metadata is currently being sent
+    pub metadata_set: bool,
+    /// Current value of the `Colorspace` property, e.g. "BT2020_RGB"
+    pub colorspace: Option<String>,
+}
+
+impl HdrState {
+    /// Whether the connector is currently driven in HDR. Drivers attach the
+    /// HDR properties to every capable connector whatever is plugged in, so
+    /// only their current values say anything about the display itself.
+    pub fn indicates_hdr(&self) -> bool {
+        self.metadata_set || self.colorspace.as_deref().is_some_and(|cs| cs.starts_with("BT2020"))
+    }
+}
+
+/// Read a connector's HDR properties from `/dev/dri/cardN`. Returns `None` if
+/// the device can't be opened (e.g. not in the video group) or the kernel
+/// doesn't expose the connector's ID in sysfs.
+pub fn connector_hdr_state(display: &DisplayInfo) -> Option<HdrState> {
+    let (device, connector_id) = open_connector(display)?;
+    let fd = device.as_raw_fd();
+
+    let mut state = HdrState::default();
+    for (prop_id, value) in object_properties(fd, connector_id)? {
+        let Some(property) = get_property(fd, prop_id) else {
+            continue;
+        };
+        match property.name.as_str() {
+            "HDR_OUTPUT_METADATA" => {
+                // A blob property; zero means no metadata is set
+                state.metadata_supported = true;
+                state.metadata_set = value != 0;
+            }
+            "Colorspace" => {
+                state.colorspace = property.enums.into_iter()
+                    .find(|(enum_value, _)| *enum_value == value)
+                    .map(|(_, name)| name);
+            }
+            _ => {}
+        }
+    }
+
+    debug_log(&format!("{}: DRM HDR state {:?}", display.connector_name, state));
+    Some(state)
+}
+
+/// Rotation of a panel mounted on its side, in degrees, from the connector's
+/// "panel orientation" property (set for built-in panels such as handhelds')
+pub fn panel_orientation(display: &DisplayInfo) -> Option<u32> {
+    let (device, connector_id) = open_connector(display)?;
+    let fd = device.as_raw_fd();
+
+    object_properties(fd, connector_id)?.into_iter().find_map(|(prop_id, value)| {
+        let property = get_property(fd, prop_id).filter(|property| property.name == "panel orientation")?;
+        let name = property.enums.into_iter().find(|(enum_value, _)| *enum_value == value)?.1;
+        debug_log(&format!("{}: panel orientation {}", display.connector_name, name));
+        orientation_degrees(&name)
+    })
+}
+
+/// Degrees for a "panel orientation" enum name, as gamescope counts them
+fn orientation_degrees(name: &str) -> Option<u32> {
+    match name {
+        "Normal" => Some(0),
+        "Left Side Up" => Some(90),
+        "Upside Down" => Some(180),
+        "Right Side Up" => Some(270),
+        _ => None,
+    }
+}
+
+/// Set a connector's "max bpc" property, the most bits per color the kernel
+/// may drive it at. Needs DRM master, so it only works while no compositor
+/// is running; gamescope keeps the value.
+pub fn set_max_bpc(display: &DisplayInfo, bpc: u32) -> Result<()> {
+    let (device, connector_id) = open_connector(display)
+        .context(format!("Could not open the DRM device for {}", display.connector_name))?;
+    let fd = device.as_raw_fd();
+
+    let prop_id = object_properties(fd, connector_id)
+        .into_iter()
+        .flatten()
+        .map(|(prop_id, _)| prop_id)
+        .find(|&prop_id| get_property(fd, prop_id).is_some_and(|property| property.name == "max bpc"))
+        .context(format!("{} has no max bpc property", display.connector_name))?;
+
+    let mut req = DrmModeObjSetProperty {
+        value: u64::from(bpc),
+        prop_id,
+        obj_id: connector_id,
+        obj_type: DRM_MODE_OBJECT_CONNECTOR,
+    };
+    if drm_ioctl(fd, DRM_IOCTL_MODE_OBJ_SETPROPERTY, &mut req).is_none() {
+        anyhow::bail!("Failed to set max bpc on {}: {} (is another compositor running?)",
+            display.connector_name, io::Error::last_os_error());
+    }
+
+    debug_log(&format!("{}: max bpc set to {}", display.connector_name, bpc));
+    Ok(())
+}
+
+/// Force a connector to YCbCr 4:2:0, or back to the driver's choice,
+/// through amdgpu's `force_yuv420_output` debugfs file. No other driver has
+/// a way to pick the format, and it needs root with debugfs mounted.
+pub fn set_ycbcr420(display: &DisplayInfo, enable: bool) -> Result<()> {
+    let path = force_yuv420_path(display)?;
+    fs::write(&path, if enable { "1" } else { "0" })
+        .context(format!("Failed to write {}", path.display()))?;
+    debug_log(&format!("{}: force_yuv420_output set to {}", display.connector_name, enable));
+    Ok(())
+}
+
+/// Whether a connector is forced to YCbCr 4:2:0, which lasts until it's
+/// switched back, even across gamescope sessions. `None` if amdgpu's switch
+/// can't be read.
+pub fn ycbcr420_forced(display: &DisplayInfo) -> Option<bool> {
+    let value = fs::read_to_string(force_yuv420_path(display).ok()?).ok()?;
+    Some(value.trim().parse::<u64>().ok()? != 0)
+}
+
+/// amdgpu's `force_yuv420_output` debugfs file for the connector
+fn force_yuv420_path(display: &DisplayInfo) -> Result<PathBuf> {
+    let (card, output) = display.connector_name.split_once('-')
+        .context(format!("Unexpected connector name {}", display.connector_name))?;
+
+    // debugfs names the device by minor number, or on newer kernels by PCI address
+    let minor = card.trim_start_matches("card").to_string();
+    let pci_slot = fs::canonicalize(Path::new(DRM_PATH).join(card).join("device"))
+        .ok()
+        .and_then(|device| device.file_name().map(|name| name.to_string_lossy().into_owned()));
+
+    [Some(minor), pci_slot]
+        .into_iter()
+        .flatten()
+        .map(|device| Path::new("/sys/kernel/debug/dri").join(device).join(output).join("force_yuv420_output"))
+        .find(|path| path.exists())
+        .context(format!("No force_yuv420_output for {} (only amdgpu has it, and it needs root with debugfs mounted)", display.connector_name))
+}
+
+/// Whether another process, such as the compositor of the session on the
+/// active VT, holds DRM master on the display's GPU, which gamescope needs.
+/// The first opener of a device becomes master if nobody is, and closing it
+/// gives that up again, so checking changes nothing. `None` if the device
+/// can't be opened.
+pub fn drm_master_held(display: &DisplayInfo) -> Option<bool> {
+    let (card, _) = display.connector_name.split_once('-')?;
+    let device = open_device(card)?;
+
+    // Like libdrm's drmIsMaster: authenticating magic 0 fails with EACCES
+    // unless we're master (EINVAL then)
+    let mut auth = DrmAuth { magic: 0 };
+    let held = drm_ioctl(device.as_raw_fd(), DRM_IOCTL_AUTH_MAGIC, &mut auth).is_none()
+        && io::Error::last_os_error().raw_os_error() == Some(libc::EACCES);
+    debug_log(&format!("{}: DRM master held by another process: {}", card, held));
+    Some(held)
+}
+
+/// Open the DRM device of a connector read-write, along with the
+/// connector's ID. `None` if the device can't be opened (e.g. not in the
+/// video group) or the kernel doesn't expose the ID in sysfs.
+fn open_connector(display: &DisplayInfo) -> Option<(File, u32)> {
+    let connector_id: u32 = fs::read_to_string(display.connector_path.join("connector_id"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    let (card, _) = display.connector_name.split_once('-')?;
+    Some((open_device(card)?, connector_id))
+}
+
+/// Open `/dev/dri/<card>` read-write
+fn open_device(card: &str) -> Option<File> {
+    let device_path = Path::new("/dev/dri").join(card);
+    match File::options().read(true).write(true).open(&device_path) {
+        Ok(device) => Some(device),
+        Err(e) => {
+            debug_log(&format!("Could not open {}: {}", device_path.display(), e));
+            None
+        }
+    }
+}
+
+// ============================================================================
+// DRM ioctl plumbing (see include/uapi/drm/drm_mode.h)
+// ============================================================================
+
+const DRM_MODE_OBJECT_CONNECTOR: u32 = 0xc0c0c0c0;
+const DRM_PROP_NAME_LEN: usize = 32;
+
+#[repr(C)]
+#[derive(Default)]
+struct DrmModeObjGetProperties {
+    props_ptr: u64,
+    prop_values_ptr: u64,
+    count_props: u32,
+    obj_id: u32,
+    obj_type: u32,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct DrmModeGetProperty {
+    values_ptr: u64,
+    enum_blob_ptr: u64,
+    prop_id: u32,
+    flags: u32,
+    name: [u8; DRM_PROP_NAME_LEN],
+    count_values: u32,
+    count_enum_blobs: u32,
+}
+
+#[repr(C)]
+struct DrmModeObjSetProperty {
+    value: u64,
+    prop_id: u32,
+    obj_id: u32,
+    obj_type: u32,
+}
+
+#[repr(C)]
+struct DrmAuth {
+    magic: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DrmModePropertyEnum {
+    value: u64,
+    name: [u8; DRM_PROP_NAME_LEN],
+}
+
+/// `_IOWR('d', nr, T)`
+const fn drm_iowr<T>(nr: u32) -> u32 {
+    (3 << 30) | ((std::mem::size_of::<T>() as u32) << 16) | ((b'd' as u32) << 8) | nr
+}
+
+/// `_IOW('d', nr, T)`
+const fn drm_iow<T>(nr: u32) -> u32 {
+    (1 << 30) | ((std::mem::size_of::<T>() as u32) << 16) | ((b'd' as u32) << 8) | nr
+}
+
+const DRM_IOCTL_AUTH_MAGIC: u32 = drm_iow::<DrmAuth>(0x11);
+const DRM_IOCTL_MODE_GETPROPERTY: u32 = drm_iowr::<DrmModeGetProperty>(0xaa);
+const DRM_IOCTL_MODE_OBJ_GETPROPERTIES: u32 = drm_iowr::<DrmModeObjGetProperties>(0xb9);
+const DRM_IOCTL_MODE_OBJ_SETPROPERTY: u32 = drm_iowr::<DrmModeObjSetProperty>(0xba);
+
+fn drm_ioctl<T>(fd: RawFd, request: u32, arg: &mut T) -> Option<()> {
+    // SAFETY: `arg` is a live #[repr(C)] struct of the type `request` expects,
+    // and any pointers in it point at buffers as long as its count fields say
+    let ret = unsafe { libc::ioctl(fd, request as _, arg as *mut T) };
+    (ret == 0).then_some(())
+}
+
+/// A property's name and, for enum properties, its possible values
+struct Property {
+    name: String,
+    enums: Vec<(u64, String)>,
+}
+
+/// IDs and current values of a connector's properties
+fn object_properties(fd: RawFd, connector_id: u32) -> Option<Vec<(u32, u64)>> {
+    let mut req = DrmModeObjGetProperties {
+        obj_id: connector_id,
+        obj_type: DRM_MODE_OBJECT_CONNECTOR,
+        ..Default::default()
+    };
+    // The first call only reports how many properties there are
+    drm_ioctl(fd, DRM_IOCTL_MODE_OBJ_GETPROPERTIES, &mut req)?;
+
+    let mut ids = vec![0u32; req.count_props as usize];
+    let mut values = vec![0u64; req.count_props as usize];
+    req.props_ptr = ids.as_mut_ptr() as u64;
+    req.prop_values_ptr = values.as_mut_ptr() as u64;
+    drm_ioctl(fd, DRM_IOCTL_MODE_OBJ_GETPROPERTIES, &mut req)?;
+
+    Some(ids.into_iter().zip(values).filter(|&(id, _)| id != 0).collect())
+}
+
+fn get_property(fd: RawFd, prop_id: u32) -> Option<Property> {
+    let mut req = DrmModeGetProperty { prop_id, ..Default::default() };
+    drm_ioctl(fd, DRM_IOCTL_MODE_GETPROPERTY, &mut req)?;
+
+    let empty = DrmModePropertyEnum { value: 0, name: [0; DRM_PROP_NAME_LEN] };
+    let mut enums = vec![empty; req.count_enum_blobs as usize];
+    if !enums.is_empty() {
+        // Skip the values, which for enums repeat what the enum entries hold
+        req.count_values = 0;
+        req.enum_blob_ptr = enums.as_mut_ptr() as u64;
+        drm_ioctl(fd, DRM_IOCTL_MODE_GETPROPERTY, &mut req)?;
+    }
+
+    Some(Property {
+        name: c_name(&req.name),
+        enums: enums.iter().map(|e| (e.value, c_name(&e.name))).collect(),
+    })
+}
+
+/// A NUL-padded name from a DRM struct
+fn c_name(name: &[u8]) -> String {
+    let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+    String::from_utf8_lossy(&name[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_active_hdr_counts() {
+        let supported_only = HdrState {
+            metadata_supported: true,
+            metadata_set: false,
+            colorspace: Some("Default".to_string()),
+        };
+        assert!(!supported_only.indicates_hdr());
+
+        let bt2020 = HdrState { colorspace: Some("BT2020_RGB".to_string()), ..supported_only.clone() };
+        assert!(bt2020.indicates_hdr());
+
+        let metadata = HdrState { metadata_set: true, ..supported_only };
+        assert!(metadata.indicates_hdr());
+    }
+
+    #[test]
+    fn panel_orientation_names_map_to_gamescope_rotations() {
+        assert_eq!(orientation_degrees("Normal"), Some(0));
+        assert_eq!(orientation_degrees("Right Side Up"), Some(270));
+        assert_eq!(orientation_degrees("Sideways"), None);
+    }
+
+    #[test]
+    fn ioctl_numbers_match_the_kernel() {
+        assert_eq!(DRM_IOCTL_MODE_GETPROPERTY, 0xc04064aa);
+        assert_eq!(DRM_IOCTL_MODE_OBJ_GETPROPERTIES, 0xc02064b9);
+        assert_eq!(DRM_IOCTL_MODE_OBJ_SETPROPERTY, 0xc01864ba);
+        assert_eq!(DRM_IOCTL_AUTH_MAGIC, 0x40046411);
+        assert_eq!(c_name(b"Colorspace\0\0\0"), "Colorspace");
+    }
+}
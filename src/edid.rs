@@ -0,0 +1,875 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::debug_log;
+use crate::display::DisplayInfo;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DisplayCapabilities {
+    pub vrr: bool,
+    pub hdr: bool,
+    pub max_refresh_rate: u32,
+    pub max_bpc: u32,
+    /// Lowest refresh rate of the VRR range, if the EDID reports one
+    pub vrr_min: Option<u32>,
+    /// Highest refresh rate of the VRR range, if the EDID reports one
+    pub vrr_max: Option<u32>,
+    /// Physical width and height of the image in mm, if the EDID reports one
+    pub physical_size_mm: Option<(u32, u32)>,
+    /// Every progressive timing the EDID lists (detailed timings and CTA-861
+    /// VICs) as (width, height, refresh), largest and fastest first
+    #[serde(default)]
+    pub modes: Vec<(u32, u32, u32)>,
+    /// HDMI sink's maximum TMDS character rate in MHz (HDMI Forum block)
+    #[serde(default)]
+    pub max_tmds_mhz: Option<u32>,
+    /// HDMI 2.1 sink's maximum FRL bandwidth in Gbps, all lanes together
+    #[serde(default)]
+    pub max_frl_gbps: Option<u32>,
+    /// Timings the sink takes in YCbCr 4:2:0 (its 4:2:0 Video and Capability
+    /// Map Data Blocks) as (width, height, refresh), largest and fastest first
+    #[serde(default)]
+    pub ycbcr420_modes: Vec<(u32, u32, u32)>,
+}
+
+impl DisplayCapabilities {
+    /// Refresh rates listed for each resolution, largest resolution first
+    pub fn refresh_rates(&self) -> Vec<((u32, u32), Vec<u32>)> {
+        let mut rates: Vec<((u32, u32), Vec<u32>)> = Vec::new();
+        for &(width, height, refresh) in &self.modes {
+            match rates.iter_mut().find(|(mode, _)| *mode == (width, height)) {
+                Some((_, list)) => list.push(refresh),
+                None => rates.push(((width, height), vec![refresh])),
+            }
+        }
+        rates
+    }
+
+    /// Refresh rates listed for one resolution, fastest first
+    pub fn refresh_rates_at(&self, width: u32, height: u32) -> Vec<u32> {
+        self.refresh_rates()
+            .into_iter()
+            .find(|&(mode, _)| mode == (width, height))
+            .map(|(_, rates)| rates)
+            .unwrap_or_default()
+    }
+
+    /// Whether an HDMI link to this sink carries a mode at `bpc` bits per
+    /// color. With an FRL rate (HDMI 2.1) that's the active pixels plus 5%
+    /// against the 16b/18b coded link, since FRL leaves out most of the
+    /// blanking; otherwise the TMDS character rate, with the ~20% blanking of
+    /// the CTA-861 4K timings (4400x2250 total for 3840x2160), against the
+    /// sink's limit (HDMI 2.0's 600 MHz when not listed). 4:2:0 halves the data.
+    pub fn hdmi_carries(&self, width: u32, height: u32, refresh_rate: u32, bpc: u32, ycbcr420: bool) -> bool {
+        let pixels_per_sec = u64::from(width) * u64::from(height) * u64::from(refresh_rate);
+        let divisor = if ycbcr420 { 2 } else { 1 };
+
+        match self.max_frl_gbps {
+            Some(gbps) => {
+                let needed = pixels_per_sec * u64::from(bpc) * 3 * 21 / 20 / divisor;
+                needed <= u64::from(gbps) * 1_000_000_000 * 16 / 18
+            }
+            None => {
+                let character_rate = pixels_per_sec * 6 / 5 * u64::from(bpc) / 8 / divisor;
+                character_rate <= u64::from(self.max_tmds_mhz.unwrap_or(600)) * 1_000_000
+            }
+        }
+    }
+
+    /// Horizontal pixel density at the given width in pixels
+    pub fn dpi(&self, width_px: u32) -> Option<f64> {
+        let (width_mm, _) = self.physical_size_mm?;
+        (width_mm > 0).then(|| f64::from(width_px) * 25.4 / f64::from(width_mm))
+    }
+}
+
+pub fn parse_edid_capabilities(edid_text: &str, display: &DisplayInfo) -> DisplayCapabilities {
+    let mut caps = parse_edid_text(edid_text);
+    apply_refresh_fallback(&mut caps, display);
+    caps
+}
+
+/// [`parse_edid_capabilities`] without the refresh rate fallback, so only
+/// what the EDID itself says; that's what the capability cache keeps
+pub fn parse_edid_text(edid_text: &str) -> DisplayCapabilities {
+    let mut caps = DisplayCapabilities {
+        vrr: false,
+        hdr: false,
+        max_refresh_rate: 60,
+        max_bpc: 8,
+        vrr_min: None,
+        vrr_max: None,
+        physical_size_mm: None,
+        modes: Vec::new(),
+        max_tmds_mhz: None,
+        max_frl_gbps: None,
+        ycbcr420_modes: Vec::new(),
+    };
+
+    // Check for VRR/FreeSync/G-SYNC
+    let vrr_patterns = [
+        "Variable Refresh Rate",
+        "FreeSync",
+        "G-SYNC Compatible",
+        "VESA VRR",
+        "Vendor-Specific Data Block (AMD)",
+    ];
+
+    for pattern in &vrr_patterns {
+        if edid_text.contains(pattern) {
+            caps.vrr = true;
+            break;
+        }
+    }
+
+    // VRR range: the FreeSync block's "Minimum/Maximum Refresh Rate: 48 Hz",
+    // HDMI's "VRRmin: 40 Hz", or failing those the range limits' "48-144 Hz"
+    if caps.vrr {
+        let rate = |pattern: &str| -> Option<u32> {
+            Regex::new(pattern).ok()?.captures(edid_text)?[1].parse().ok()
+        };
+        let range = rate(r"Minimum Refresh Rate: (\d+) Hz")
+            .zip(rate(r"Maximum Refresh Rate: (\d+) Hz"))
+            .or_else(|| rate(r"VRRmin: (\d+) Hz").zip(rate(r"VRRmax: (\d+) Hz")))
+            .or_else(|| {
+                let cap = Regex::new(r"(\d+)\s*-\s*(\d+)\s*Hz").ok()?.captures(edid_text)?;
+                Some((cap[1].parse().ok()?, cap[2].parse().ok()?))
+            });
+        if let Some((min, max)) = range.filter(|(min, max)| min < max) {
+            caps.vrr_min = Some(min);
+            caps.vrr_max = Some(max);
+        }
+    }
+
+    // Check for HDR
+    let hdr_patterns = [
+        "HDR Static Metadata",
+        "HDR10",
+        "SMPTE ST 2084",
+    ];
+
+    for pattern in &hdr_patterns {
+        if edid_text.contains(pattern) {
+            caps.hdr = true;
+            break;
+        }
+    }
+
+    // Check for color depth
+    if edid_text.contains("12 bits per") || edid_text.contains("Bits per primary color channel: 12") {
+        caps.max_bpc = 12;
+    } else if edid_text.contains("10 bits per") || edid_text.contains("Bits per primary color channel: 10") {
+        caps.max_bpc = 10;
+    }
+
+    // Extract maximum refresh rate, only from lines describing a timing
+    // ("DTD 1:  3840x2160   60.000000 Hz", "VIC  63:  1920x1080  120.000000 Hz",
+    // or older "Vertical Freq: 60.000 Hz") so that range limits and clock
+    // figures elsewhere in the output aren't mistaken for refresh rates
+    let refresh_regex = Regex::new(r"(?:\d+x\d+\S*\s+|Vertical Freq\S*\s*)(\d+)(?:\.\d+)?\s*Hz").ok();
+    if let Some(re) = refresh_regex {
+        let mut max_rate = 0;
+        for cap in re.captures_iter(edid_text) {
+            if let Ok(rate) = cap[1].parse::<u32>() {
+                if rate > max_rate && rate <= 500 {  // Sanity check
+                    max_rate = rate;
+                }
+            }
+        }
+        caps.max_refresh_rate = max_rate;
+    }
+
+    // Physical size: prefer the first detailed timing's "(597 mm x 336 mm)",
+    // falling back to the coarser "Maximum image size: 60 cm x 34 cm"
+    let size = |pattern: &str, scale: u32| -> Option<(u32, u32)> {
+        let cap = Regex::new(pattern).ok()?.captures(edid_text)?;
+        let width = cap[1].parse::<u32>().ok()? * scale;
+        let height = cap[2].parse::<u32>().ok()? * scale;
+        (width > 0 && height > 0).then_some((width, height))
+    };
+    caps.physical_size_mm = size(r"\((\d+) mm x (\d+) mm\)", 1)
+        .or_else(|| size(r"Maximum image size: (\d+) cm x (\d+) cm", 10));
+
+    // HDMI link limits from the HDMI Forum block: "Maximum TMDS Character
+    // Rate: 600 MHz", and "Max Fixed Rate Link: 3 and 6 Gbps per lane on 3
+    // lanes, 6, 8 and 10 Gbps per lane on 4 lanes" whose fastest rate times
+    // its lanes is the FRL bandwidth (the DSC line has a prefix, so it's left out)
+    caps.max_tmds_mhz = Regex::new(r"Maximum TMDS Character Rate: (\d+) MHz")
+        .ok()
+        .and_then(|re| re.captures(edid_text)?[1].parse().ok());
+    let frl_line = Regex::new(r"(?m)^\s*Max Fixed Rate Link: (.*)$").ok().and_then(|re| re.captures(edid_text));
+    let frl_rate = Regex::new(r"(\d+) Gbps per lane on (\d+) lanes").ok();
+    if let (Some(line), Some(re)) = (frl_line, frl_rate) {
+        caps.max_frl_gbps = re
+            .captures_iter(&line[1])
+            .filter_map(|cap| Some(cap[1].parse::<u32>().ok()? * cap[2].parse::<u32>().ok()?))
+            .max();
+    }
+
+    // Every listed timing, e.g. "VIC 117:  3840x2160  100.000000 Hz";
+    // interlaced ones ("1920x1080i") are left out
+    if let Ok(re) = Regex::new(r"(\d+)x(\d+)\s+(\d+(?:\.\d+)?)\s*Hz") {
+        let timings = |text: &str| -> Vec<(u32, u32, u32)> {
+            re.captures_iter(text).filter_map(|cap| {
+                let refresh = cap[3].parse::<f64>().ok()?.round() as u32;
+                Some((cap[1].parse().ok()?, cap[2].parse().ok()?, refresh))
+            }).collect()
+        };
+        caps.modes = sorted_modes(timings(edid_text).into_iter());
+
+        // 4:2:0 timings are listed under their own blocks; a capability map
+        // of "All VDB SVDs" covers every timing of the Video Data Block
+        let map = cta_section(edid_text, "YCbCr 4:2:0 Capability Map Data Block");
+        let mut ycbcr420 = timings(&cta_section(edid_text, "YCbCr 4:2:0 Video Data Block"));
+        ycbcr420.extend(timings(&map));
+        if map.contains("All VDB SVDs") {
+            ycbcr420.extend(timings(&cta_section(edid_text, "Video Data Block")));
+        }
+        caps.ycbcr420_modes = sorted_modes(ycbcr420.into_iter());
+    }
+
+    caps
+}
+
+/// Assume a refresh rate from the resolution if the EDID didn't give a good one
+pub fn apply_refresh_fallback(caps: &mut DisplayCapabilities, display: &DisplayInfo) {
+    if caps.max_refresh_rate < 60 {
+        caps.max_refresh_rate = if display.width >= 2560 { 144 } else { 60 };
+    }
+}
+
+/// The lines of an edid-decode data block, e.g. everything indented under
+/// "  Video Data Block:", or an empty string if there's no such block
+fn cta_section(edid_text: &str, title: &str) -> String {
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let mut lines = edid_text.lines().skip_while(|line| line.trim().strip_suffix(':') != Some(title));
+    let Some(header) = lines.next() else {
+        return String::new();
+    };
+    lines
+        .take_while(|line| indent(line) > indent(header))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+/// Whether edid-decode reported a block checksum that doesn't match,
+/// e.g. "Checksum: 0x2e (should be 0x2d)"
+pub fn edid_decode_checksum_error(edid_text: &str) -> bool {
+    Regex::new(r"(?i)checksum[^\n]*(should be|invalid)")
+        .map(|re| re.is_match(edid_text))
+        .unwrap_or(false)
+}
+
+pub fn default_capabilities(display: &DisplayInfo) -> DisplayCapabilities {
+    DisplayCapabilities {
+        vrr: false,
+        hdr: false,
+        max_refresh_rate: if display.width >= 2560 { 144 } else { 60 },
+        max_bpc: 8,
+        vrr_min: None,
+        vrr_max: None,
+        physical_size_mm: None,
+        modes: Vec::new(),
+        max_tmds_mhz: None,
+        max_frl_gbps: None,
+        ycbcr420_modes: Vec::new(),
+    }
+}
+
+/// Deduplicate modes and sort them by size, then refresh rate, descending,
+/// dropping implausible refresh rates
+fn sorted_modes(modes: impl Iterator<Item = (u32, u32, u32)>) -> Vec<(u32, u32, u32)> {
+    let mut modes: Vec<_> = modes.filter(|&(_, _, refresh)| refresh > 0 && refresh <= 500).collect();
+    modes.sort_by_key(|&(width, height, refresh)| std::cmp::Reverse((u64::from(width) * u64::from(height), width, refresh)));
+    modes.dedup();
+    modes
+}
+
+/// Highest refresh rate among the detailed timing descriptors in the EDID base block
+pub fn edid_max_refresh(edid: &[u8]) -> Option<u32> {
+    if edid.len() < 128 {
+        return None;
+    }
+
+    // The base block holds four 18-byte descriptors starting at offset 54
+    (0..4)
+        .filter_map(|i| dtd_refresh(&edid[54 + i * 18..72 + i * 18]))
+        .max()
+}
+
+/// The preferred ("native") timing: the first detailed timing descriptor,
+/// if it's a progressive one
+pub fn edid_preferred_mode(edid: &[u8]) -> Option<(u32, u32)> {
+    if edid.len() < 128 {
+        return None;
+    }
+    dtd_mode(&edid[54..72]).map(|(width, height, _)| (width, height))
+}
+
+/// Monitor name from the EDID's display name descriptor, e.g. "LG TV SSCR2"
+pub fn edid_monitor_name(edid: &[u8]) -> Option<String> {
+    descriptor_text(edid, 0xfc)
+}
+
+/// Serial number string from the EDID's serial descriptor, if it has one
+pub fn edid_serial(edid: &[u8]) -> Option<String> {
+    descriptor_text(edid, 0xff)
+}
+
+/// Text of the first base block display descriptor with this tag: up to 13
+/// characters, ended by a newline and padded with spaces
+fn descriptor_text(edid: &[u8], tag: u8) -> Option<String> {
+    if edid.len() < 128 {
+        return None;
+    }
+
+    (0..4)
+        .map(|i| &edid[54 + i * 18..72 + i * 18])
+        .find(|descriptor| descriptor[..3] == [0, 0, 0] && descriptor[3] == tag)
+        .map(|descriptor| {
+            let text = &descriptor[5..];
+            let end = text.iter().position(|&b| b == b'\n').unwrap_or(text.len());
+            String::from_utf8_lossy(&text[..end]).trim().to_string()
+        })
+        .filter(|text| !text.is_empty())
+}
+
+/// Physical image size in mm from the first detailed timing descriptor,
+/// falling back to the base block's size in cm
+fn edid_physical_size(edid: &[u8]) -> Option<(u32, u32)> {
+    let dtd = &edid[54..72];
+    let dtd_size = dtd_refresh(dtd).map(|_| {
+        let width = u32::from(dtd[12]) | (u32::from(dtd[14] >> 4) << 8);
+        let height = u32::from(dtd[13]) | (u32::from(dtd[14] & 0x0f) << 8);
+        (width, height)
+    });
+    let base_size = (u32::from(edid[21]) * 10, u32::from(edid[22]) * 10);
+
+    [dtd_size, Some(base_size)]
+        .into_iter()
+        .flatten()
+        .find(|&(width, height)| width > 0 && height > 0)
+}
+
+/// Width, height and refresh rate of a progressive detailed timing descriptor
+fn dtd_mode(dtd: &[u8]) -> Option<(u32, u32, u32)> {
+    let refresh = dtd_refresh(dtd)?;
+    if dtd[17] & 0x80 != 0 {
+        return None;
+    }
+    let width = u32::from(dtd[2]) | (u32::from(dtd[4] >> 4) << 8);
+    let height = u32::from(dtd[5]) | (u32::from(dtd[7] >> 4) << 8);
+    Some((width, height, refresh))
+}
+
+/// Refresh rate of an 18-byte detailed timing descriptor; a zero pixel
+/// clock marks a display descriptor instead of a timing
+fn dtd_refresh(dtd: &[u8]) -> Option<u32> {
+    let pixel_clock = u64::from(u16::from_le_bytes([dtd[0], dtd[1]])) * 10_000;
+    if pixel_clock == 0 {
+        return None;
+    }
+    let h_total = u64::from(dtd[2]) + (u64::from(dtd[4] >> 4) << 8)
+        + u64::from(dtd[3]) + (u64::from(dtd[4] & 0x0f) << 8);
+    let v_total = u64::from(dtd[5]) + (u64::from(dtd[7] >> 4) << 8)
+        + u64::from(dtd[6]) + (u64::from(dtd[7] & 0x0f) << 8);
+    if h_total == 0 || v_total == 0 {
+        return None;
+    }
+    Some(((pixel_clock + (h_total * v_total) / 2) / (h_total * v_total)) as u32)
+}
+
+// ============================================================================
+// Native EDID parsing (used when edid-decode isn't installed)
+// ============================================================================
+
+const EDID_HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+const OUI_AMD: [u8; 3] = [0x1a, 0x00, 0x00];
+const OUI_HDMI_FORUM: [u8; 3] = [0xd8, 0x5d, 0xc4];
+
+/// FRL bandwidth in Gbps, all lanes together, for each Max_FRL_Rate of the
+/// HDMI Forum block: 3 lanes at 3 or 6 Gbps, then 4 lanes at 6 to 12 Gbps
+const MAX_FRL_GBPS: [u32; 7] = [0, 9, 18, 24, 32, 40, 48];
+
+/// Parse capabilities straight from raw EDID bytes, covering the same ground
+/// as [`parse_edid_capabilities`] does with edid-decode output.
+/// Returns `None` if the data isn't an EDID at all.
+pub fn parse_edid_bytes(edid: &[u8], display: &DisplayInfo) -> Option<DisplayCapabilities> {
+    if edid.len() < 128 || edid[..8] != EDID_HEADER {
+        return None;
+    }
+
+    let mut caps = DisplayCapabilities {
+        vrr: false,
+        hdr: false,
+        max_refresh_rate: edid_max_refresh(edid).unwrap_or(0),
+        max_bpc: 8,
+        vrr_min: None,
+        vrr_max: None,
+        physical_size_mm: edid_physical_size(edid),
+        modes: Vec::new(),
+        max_tmds_mhz: None,
+        max_frl_gbps: None,
+        ycbcr420_modes: Vec::new(),
+    };
+    let mut modes: Vec<(u32, u32, u32)> = (0..4)
+        .filter_map(|i| dtd_mode(&edid[54 + i * 18..72 + i * 18]))
+        .collect();
+
+    // EDID 1.4 digital inputs report bits per primary color in byte 20
+    if edid[18] == 1 && edid[19] >= 4 && edid[20] & 0x80 != 0 {
+        caps.max_bpc = match (edid[20] >> 4) & 0x07 {
+            3 => 10,
+            4..=6 => 12,
+            _ => 8,
+        };
+    }
+
+    for block in edid[128..].chunks_exact(128).filter(|block| block[0] == 0x02) {
+        parse_cta_block(block, &mut caps, &mut modes);
+    }
+    caps.modes = sorted_modes(modes.into_iter());
+    caps.ycbcr420_modes = sorted_modes(std::mem::take(&mut caps.ycbcr420_modes).into_iter());
+
+    if let Some((min, max)) = vrr_range(edid) {
+        debug_log(&format!("Native EDID parser: VRR range {}-{} Hz", min, max));
+        caps.vrr = true;
+        caps.vrr_min = Some(min);
+        caps.vrr_max = Some(max);
+    }
+
+    if caps.max_refresh_rate < 60 || caps.max_refresh_rate > 500 {
+        caps.max_refresh_rate = if display.width >= 2560 { 144 } else { 60 };
+    }
+
+    Some(caps)
+}
+
+/// Pick up HDR support and timings from a CTA-861 extension block
+fn parse_cta_block(block: &[u8], caps: &mut DisplayCapabilities, modes: &mut Vec<(u32, u32, u32)>) {
+    // Byte 2 is where the detailed timings start, data blocks sit before it
+    let dtd_start = usize::from(block[2]).clamp(4, 127);
+    let mut svds = Vec::new();
+    let mut ycbcr420_map = None;
+
+    for data_block in cta_data_blocks(block) {
+        match data_block {
+            (2, vics) => {
+                for &vic in vics {
+                    let vic = svd_vic(vic);
+                    if let Some(rate) = vic_refresh(vic) {
+                        caps.max_refresh_rate = caps.max_refresh_rate.max(rate);
+                    }
+                    modes.extend(vic_mode(vic));
+                    svds.push(vic);
+                }
+            }
+            // YCbCr 4:2:0 Video Data Block: timings the sink only takes in 4:2:0
+            (7, [0x0e, vics @ ..]) => {
+                let vics: Vec<_> = vics.iter().filter_map(|&vic| vic_mode(svd_vic(vic))).collect();
+                modes.extend(&vics);
+                caps.ycbcr420_modes.extend(vics);
+            }
+            // YCbCr 4:2:0 Capability Map: a bit for each Video Data Block
+            // timing, in order
+            (7, [0x0f, map @ ..]) => ycbcr420_map = Some(map),
+            // HDR Static Metadata Data Block: any EOTF beyond traditional SDR gamma
+            (7, [0x06, eotf, ..]) if eotf & 0x0e != 0 => caps.hdr = true,
+            // HDMI Forum block: TMDS character rate in 5 MHz units, and the
+            // FRL rate in the high nibble two bytes after it
+            (3, [oui0, oui1, oui2, _, max_tmds, _, frl, ..]) if [*oui0, *oui1, *oui2] == OUI_HDMI_FORUM => {
+                caps.max_tmds_mhz = (*max_tmds != 0).then(|| u32::from(*max_tmds) * 5);
+                caps.max_frl_gbps = MAX_FRL_GBPS.get(usize::from(frl >> 4)).copied().filter(|&gbps| gbps > 0);
+            }
+            _ => {}
+        }
+    }
+
+    // An empty map covers every timing
+    if let Some(map) = ycbcr420_map {
+        let mapped = |i: usize| map.is_empty() || map.get(i / 8).is_some_and(|bits| bits & (1 << (i % 8)) != 0);
+        caps.ycbcr420_modes.extend(
+            svds.iter().enumerate().filter(|&(i, _)| mapped(i)).filter_map(|(_, &vic)| vic_mode(vic)),
+        );
+    }
+
+    if block[2] != 0 {
+        for dtd in block[dtd_start..127].chunks_exact(18) {
+            if let Some(rate) = dtd_refresh(dtd) {
+                caps.max_refresh_rate = caps.max_refresh_rate.max(rate);
+            }
+            modes.extend(dtd_mode(dtd));
+        }
+    }
+}
+
+/// VRR range advertised by an AMD FreeSync or HDMI Forum data block
+fn vrr_range(edid: &[u8]) -> Option<(u32, u32)> {
+    edid[128..]
+        .chunks_exact(128)
+        .filter(|block| block[0] == 0x02)
+        .flat_map(cta_data_blocks)
+        .find_map(|(tag, payload)| match (tag, payload) {
+            (3, [oui0, oui1, oui2, _, _, min, max, ..]) if [*oui0, *oui1, *oui2] == OUI_AMD => {
+                (max > min).then_some((u32::from(*min), u32::from(*max)))
+            }
+            (3, [oui0, oui1, oui2, _, _, _, _, _, vrr_min, vrr_max, ..])
+                if [*oui0, *oui1, *oui2] == OUI_HDMI_FORUM =>
+            {
+                let min = u32::from(vrr_min & 0x3f);
+                let max = (u32::from(vrr_min & 0xc0) << 2) | u32::from(*vrr_max);
+                (max > min).then_some((min, max))
+            }
+            _ => None,
+        })
+}
+
+/// Iterate a CTA-861 block's data blocks as (tag, payload) pairs
+fn cta_data_blocks(block: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    let end = usize::from(block[2]).clamp(4, 127);
+    let mut offset = 4;
+    std::iter::from_fn(move || {
+        if offset >= end {
+            return None;
+        }
+        let header = block[offset];
+        let len = usize::from(header & 0x1f);
+        let payload = block.get(offset + 1..offset + 1 + len)?;
+        offset += 1 + len;
+        Some((header >> 5, payload))
+    })
+}
+
+/// Refresh rate of the CTA-861 VICs above 60 Hz; 60 Hz and below is
+/// covered by the default anyway. SD modes at up to 240 Hz say nothing
+/// about what the panel does at its own resolution, so they don't count.
+fn vic_refresh(vic: u8) -> Option<u32> {
+    vic_mode(vic)
+        .filter(|&(width, _, refresh)| width >= 1280 && refresh > 60)
+        .map(|(_, _, refresh)| refresh)
+}
+
+/// The VIC of a short video descriptor: 129-192 are native-flagged copies of
+/// VICs 1-64
+fn svd_vic(svd: u8) -> u8 {
+    if (129..=192).contains(&svd) { svd & 0x7f } else { svd }
+}
+
+/// Width, height and (rounded) refresh rate of the progressive CTA-861 VICs
+fn vic_mode(vic: u8) -> Option<(u32, u32, u32)> {
+    let mode = match vic {
+        1 => (640, 480, 60),
+        2 | 3 => (720, 480, 60),
+        4 | 69 => (1280, 720, 60),
+        14 | 15 => (1440, 480, 60),
+        16 | 76 => (1920, 1080, 60),
+        17 | 18 => (720, 576, 50),
+        19 | 68 => (1280, 720, 50),
+        29 | 30 => (1440, 576, 50),
+        31 | 75 => (1920, 1080, 50),
+        32 | 72 => (1920, 1080, 24),
+        33 | 73 => (1920, 1080, 25),
+        34 | 74 => (1920, 1080, 30),
+        35 | 36 => (2880, 480, 60),
+        37 | 38 => (2880, 576, 50),
+        41 | 70 => (1280, 720, 100),
+        42 | 43 => (720, 576, 100),
+        47 | 71 => (1280, 720, 120),
+        48 | 49 => (720, 480, 120),
+        52 | 53 => (720, 576, 200),
+        56 | 57 => (720, 480, 240),
+        60 | 65 => (1280, 720, 24),
+        61 | 66 => (1280, 720, 25),
+        62 | 67 => (1280, 720, 30),
+        63 | 78 => (1920, 1080, 120),
+        64 | 77 => (1920, 1080, 100),
+        79 => (1680, 720, 24),
+        80 => (1680, 720, 25),
+        81 => (1680, 720, 30),
+        82 => (1680, 720, 50),
+        83 => (1680, 720, 60),
+        84 => (1680, 720, 100),
+        85 => (1680, 720, 120),
+        86 => (2560, 1080, 24),
+        87 => (2560, 1080, 25),
+        88 => (2560, 1080, 30),
+        89 => (2560, 1080, 50),
+        90 => (2560, 1080, 60),
+        91 => (2560, 1080, 100),
+        92 => (2560, 1080, 120),
+        93 | 103 => (3840, 2160, 24),
+        94 | 104 => (3840, 2160, 25),
+        95 | 105 => (3840, 2160, 30),
+        96 | 106 => (3840, 2160, 50),
+        97 | 107 => (3840, 2160, 60),
+        98 => (4096, 2160, 24),
+        99 => (4096, 2160, 25),
+        100 => (4096, 2160, 30),
+        101 => (4096, 2160, 50),
+        102 => (4096, 2160, 60),
+        108 | 109 => (1280, 720, 48),
+        110 => (1680, 720, 48),
+        111 | 112 => (1920, 1080, 48),
+        113 => (2560, 1080, 48),
+        114 | 116 => (3840, 2160, 48),
+        115 => (4096, 2160, 48),
+        117 | 119 => (3840, 2160, 100),
+        118 | 120 => (3840, 2160, 120),
+        121 => (5120, 2160, 24),
+        122 => (5120, 2160, 25),
+        123 => (5120, 2160, 30),
+        124 => (5120, 2160, 48),
+        125 => (5120, 2160, 50),
+        126 => (5120, 2160, 60),
+        127 => (5120, 2160, 100),
+        193 => (5120, 2160, 120),
+        194 | 202 => (7680, 4320, 24),
+        195 | 203 => (7680, 4320, 25),
+        196 | 204 => (7680, 4320, 30),
+        197 | 205 => (7680, 4320, 48),
+        198 | 206 => (7680, 4320, 50),
+        199 | 207 => (7680, 4320, 60),
+        200 | 208 => (7680, 4320, 100),
+        201 | 209 => (7680, 4320, 120),
+        210 => (10240, 4320, 24),
+        211 => (10240, 4320, 25),
+        212 => (10240, 4320, 30),
+        213 => (10240, 4320, 48),
+        214 => (10240, 4320, 50),
+        215 => (10240, 4320, 60),
+        216 => (10240, 4320, 100),
+        217 => (10240, 4320, 120),
+        218 => (4096, 2160, 100),
+        219 => (4096, 2160, 120),
+        _ => return None,
+    };
+    Some(mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn display(width: u32, height: u32) -> DisplayInfo {
+        DisplayInfo {
+            connector_name: "card1-DP-1".to_string(),
+            connector_path: PathBuf::from("/sys/class/drm/card1-DP-1"),
+            resolution: format!("{}x{}", width, height),
+            width,
+            height,
+            available_modes: vec![(width, height)],
+            output_ambiguous: false,
+            gpu_id: None,
+            modes_unavailable: false,
+            model: None,
+            serial: None,
+            native_mode: None,
+        }
+    }
+
+    #[test]
+    fn hdr_tv() {
+        let caps = parse_edid_capabilities(include_str!("../tests/fixtures/edid-decode/hdr-tv.txt"), &display(3840, 2160));
+        assert!(caps.hdr);
+        assert!(!caps.vrr);
+        assert_eq!(caps.max_bpc, 10);
+        assert_eq!(caps.max_refresh_rate, 120);
+        assert_eq!(caps.physical_size_mm, Some((1600, 900)));
+        assert_eq!(caps.refresh_rates()[0], ((3840, 2160), vec![120, 100, 60]));
+        assert_eq!(caps.refresh_rates_at(3840, 2160), [120, 100, 60]);
+        assert!(caps.refresh_rates_at(1234, 567).is_empty());
+        assert_eq!((caps.max_tmds_mhz, caps.max_frl_gbps), (Some(600), Some(40)));
+        assert_eq!(caps.ycbcr420_modes, [(3840, 2160, 120), (3840, 2160, 100)]);
+    }
+
+    #[test]
+    fn ycbcr420_capability_map_can_cover_every_timing() {
+        let text = "  Video Data Block:\n\
+                    \x20   VIC  97:  3840x2160   60.000000 Hz  16:9   135.000 kHz 594.000000 MHz\n\
+                    \x20 YCbCr 4:2:0 Capability Map Data Block:\n\
+                    \x20   All VDB SVDs\n";
+        let caps = parse_edid_capabilities(text, &display(3840, 2160));
+        assert_eq!(caps.ycbcr420_modes, [(3840, 2160, 60)]);
+        assert!(parse_edid_capabilities("", &display(3840, 2160)).ycbcr420_modes.is_empty());
+    }
+
+    #[test]
+    fn frl_rate_is_the_fastest_lane_setup() {
+        let text = "    Max Fixed Rate Link: 3 and 6 Gbps per lane on 3 lanes\n\
+                    DSC Max Fixed Rate Link: 3 and 6 Gbps per lane on 3 lanes, 6 Gbps per lane on 4 lanes\n";
+        let caps = parse_edid_capabilities(text, &display(3840, 2160));
+        assert_eq!(caps.max_frl_gbps, Some(18));
+
+        let caps = parse_edid_capabilities("    Max Fixed Rate Link: Not Supported\n", &display(3840, 2160));
+        assert_eq!(caps.max_frl_gbps, None);
+    }
+
+    #[test]
+    fn hdmi_2_0_fits_4k60_rgb_but_not_4k120() {
+        // No HDMI Forum block: HDMI 2.0's 600 MHz
+        let caps = DisplayCapabilities::default();
+        assert!(caps.hdmi_carries(3840, 2160, 60, 8, false));
+        assert!(!caps.hdmi_carries(3840, 2160, 60, 10, false));
+        assert!(!caps.hdmi_carries(3840, 2160, 120, 8, false));
+        assert!(caps.hdmi_carries(2560, 1440, 120, 8, false));
+    }
+
+    #[test]
+    fn hdmi_link_bandwidth_limits_bit_depth() {
+        // 40 Gbps FRL: 4K120 fits at 10-bit but not 12-bit
+        let frl = DisplayCapabilities { max_frl_gbps: Some(40), ..Default::default() };
+        assert!(frl.hdmi_carries(3840, 2160, 120, 10, false));
+        assert!(!frl.hdmi_carries(3840, 2160, 120, 12, false));
+
+        // HDMI 2.0: 4K60 only fits 10-bit in 4:2:0
+        let tmds = DisplayCapabilities { max_tmds_mhz: Some(600), ..Default::default() };
+        assert!(tmds.hdmi_carries(3840, 2160, 60, 8, false));
+        assert!(!tmds.hdmi_carries(3840, 2160, 60, 10, false));
+        assert!(tmds.hdmi_carries(3840, 2160, 60, 10, true));
+    }
+
+    #[test]
+    fn freesync_monitor() {
+        let caps = parse_edid_capabilities(include_str!("../tests/fixtures/edid-decode/freesync-monitor.txt"), &display(2560, 1440));
+        assert!(caps.vrr);
+        assert!(!caps.hdr);
+        assert_eq!(caps.max_bpc, 8);
+        assert_eq!(caps.max_refresh_rate, 144);
+        assert_eq!((caps.vrr_min, caps.vrr_max), (Some(48), Some(144)));
+    }
+
+    #[test]
+    fn checksum_errors_are_detected() {
+        assert!(edid_decode_checksum_error("Block 0, Base EDID:\n  Checksum: 0x2e (should be 0x2d)\n"));
+        assert!(!edid_decode_checksum_error(include_str!("../tests/fixtures/edid-decode/hdr-tv.txt")));
+        assert!(!edid_decode_checksum_error(include_str!("../tests/fixtures/edid-decode/freesync-monitor.txt")));
+    }
+
+    #[test]
+    fn vrr_range_from_range_limits() {
+        let text = "Vendor-Specific Data Block (AMD), OUI 00-00-1A:\n  \
+                    Monitor ranging (Range Limits Only): 48-120 Hz V, 30-160 kHz H, max dotclock 600 MHz\n";
+        let caps = parse_edid_capabilities(text, &display(2560, 1440));
+        assert_eq!((caps.vrr_min, caps.vrr_max), (Some(48), Some(120)));
+    }
+
+    #[test]
+    fn no_vrr_range_without_vrr() {
+        // The TV lists HDMI VRRmin/VRRmax and range limits but no VRR support we act on
+        let caps = parse_edid_capabilities(include_str!("../tests/fixtures/edid-decode/hdr-tv.txt"), &display(3840, 2160));
+        assert_eq!((caps.vrr_min, caps.vrr_max), (None, None));
+    }
+
+    #[test]
+    fn plain_1080p_panel() {
+        let caps = parse_edid_capabilities(include_str!("../tests/fixtures/edid-decode/laptop-1080p.txt"), &display(1920, 1080));
+        assert!(!caps.vrr);
+        assert!(!caps.hdr);
+        assert_eq!(caps.max_bpc, 8);
+        assert_eq!(caps.max_refresh_rate, 60);
+        assert_eq!(caps.physical_size_mm, Some((344, 194)));
+        assert_eq!(caps.dpi(1920).map(f64::round), Some(142.0));
+    }
+
+    #[test]
+    fn range_limits_are_not_refresh_rates() {
+        // "24-400 Hz V" in the range limits descriptor must not win over the 60 Hz DTD
+        let caps = parse_edid_capabilities(include_str!("../tests/fixtures/edid-decode/bogus-range-limits.txt"), &display(1920, 1080));
+        assert_eq!(caps.max_refresh_rate, 60);
+    }
+
+    /// EDID 1.4, 10 bpc DisplayPort, 2560x1440@144 DTD, plus a CTA-861 block
+    /// with VICs 16/63, an AMD FreeSync 48-144 Hz block and HDR static metadata
+    fn sample_edid() -> Vec<u8> {
+        let mut edid = vec![0u8; 256];
+        edid[..8].copy_from_slice(&EDID_HEADER);
+        edid[18] = 1;
+        edid[19] = 4;
+        edid[20] = 0xb5;
+        edid[21] = 60;
+        edid[22] = 34;
+        edid[54..62].copy_from_slice(&[0x23, 0xe5, 0x00, 0xa0, 0xa0, 0xa0, 0x39, 0x50]);
+        // 597 x 336 mm
+        edid[66..69].copy_from_slice(&[0x55, 0x50, 0x21]);
+        edid[126] = 1;
+
+        let cta = [
+            0x02, 0x03, 19, 0x00,
+            0x42, 16, 63,
+            0x67, 0x1a, 0x00, 0x00, 0x01, 0x01, 48, 144,
+            0xe3, 0x06, 0x05, 0x01,
+        ];
+        edid[128..128 + cta.len()].copy_from_slice(&cta);
+        edid
+    }
+
+    #[test]
+    fn native_parser_reads_vrr_hdr_and_bpc() {
+        let caps = parse_edid_bytes(&sample_edid(), &display(2560, 1440)).unwrap();
+        assert!(caps.vrr);
+        assert!(caps.hdr);
+        assert_eq!(caps.max_bpc, 10);
+        assert_eq!(caps.max_refresh_rate, 144);
+        assert_eq!(caps.physical_size_mm, Some((597, 336)));
+        assert_eq!((caps.vrr_min, caps.vrr_max), (Some(48), Some(144)));
+        assert_eq!(caps.modes, [(2560, 1440, 144), (1920, 1080, 120), (1920, 1080, 60)]);
+    }
+
+    #[test]
+    fn native_parser_reads_hdmi_forum_link_limits() {
+        // HDMI Forum block: 600 MHz TMDS, FRL at up to 10 Gbps on 4 lanes
+        let mut edid = sample_edid();
+        edid[128 + 19..128 + 27].copy_from_slice(&[0x67, 0xd8, 0x5d, 0xc4, 0x01, 120, 0x80, 0x50]);
+        edid[128 + 2] = 27;
+        let caps = parse_edid_bytes(&edid, &display(3840, 2160)).unwrap();
+        assert_eq!((caps.max_tmds_mhz, caps.max_frl_gbps), (Some(600), Some(40)));
+
+        let caps = parse_edid_bytes(&sample_edid(), &display(2560, 1440)).unwrap();
+        assert_eq!((caps.max_tmds_mhz, caps.max_frl_gbps), (None, None));
+    }
+
+    #[test]
+    fn native_parser_reads_ycbcr420_blocks() {
+        // 4:2:0 Capability Map for VIC 63, and a 4:2:0-only VIC 97
+        let mut edid = sample_edid();
+        edid[128 + 19..128 + 25].copy_from_slice(&[0xe2, 0x0f, 0x02, 0xe2, 0x0e, 97]);
+        edid[128 + 2] = 25;
+        let caps = parse_edid_bytes(&edid, &display(3840, 2160)).unwrap();
+        assert_eq!(caps.ycbcr420_modes, [(3840, 2160, 60), (1920, 1080, 120)]);
+        assert_eq!(caps.modes[0], (3840, 2160, 60));
+
+        // An empty map covers the whole Video Data Block
+        edid[128 + 19..128 + 21].copy_from_slice(&[0xe1, 0x0f]);
+        edid[128 + 2] = 21;
+        let caps = parse_edid_bytes(&edid, &display(1920, 1080)).unwrap();
+        assert_eq!(caps.ycbcr420_modes, [(1920, 1080, 120), (1920, 1080, 60)]);
+    }
+
+    #[test]
+    fn native_parser_uses_vics_without_dtds() {
+        let mut edid = sample_edid();
+        edid[54..72].fill(0);
+        let caps = parse_edid_bytes(&edid, &display(1920, 1080)).unwrap();
+        assert_eq!(caps.max_refresh_rate, 120);
+        assert_eq!(caps.physical_size_mm, Some((600, 340)));
+    }
+
+    #[test]
+    fn native_parser_rejects_non_edid_data() {
+        assert!(parse_edid_bytes(&[0u8; 256], &display(1920, 1080)).is_none());
+        assert!(parse_edid_bytes(&EDID_HEADER, &display(1920, 1080)).is_none());
+    }
+
+    #[test]
+    fn no_detailed_timing_falls_back_to_resolution() {
+        let text = include_str!("../tests/fixtures/edid-decode/no-detailed-timing.txt");
+        assert_eq!(parse_edid_capabilities(text, &display(2560, 1440)).max_refresh_rate, 144);
+        assert_eq!(parse_edid_capabilities(text, &display(1920, 1080)).max_refresh_rate, 60);
+        // What gets cached leaves the fallback out
+        assert_eq!(parse_edid_text(text).max_refresh_rate, 0);
+        // Without a detailed timing, the size comes from the base block in cm
+        assert_eq!(parse_edid_capabilities(text, &display(1920, 1080)).physical_size_mm, Some((880, 500)));
+    }
+}
@@ -0,0 +1,117 @@
+//! Errors callers may want to tell apart: display detection, mode parsing,
+//! launcher and gamescope failures. The binary wraps them in `anyhow`.
+
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::string::FromUtf8Error;
+use thiserror::Error;
+
+use crate::gamescope::LaunchMode;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The DRM sysfs tree couldn't be read
+    #[error("Failed to read {}", .path.display())]
+    DisplayDetection {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Display '{0}' not found")]
+    DisplayNotFound(String),
+
+    /// Several displays' EDID monitor names match the --display text
+    #[error("'{name}' matches several displays ({}); use the connector name", .connectors.join(", "))]
+    AmbiguousDisplay { name: String, connectors: Vec<String> },
+
+    /// Not `WIDTHxHEIGHT`
+    #[error("Invalid resolution format: {0}")]
+    InvalidResolution(String),
+
+    /// The part after `@` in a mode isn't a rate of at least 1 Hz
+    #[error("Invalid refresh rate in mode: {0}")]
+    InvalidRefreshRate(String),
+
+    #[error("Invalid GPU '{0}' (expected e.g. card1 or /dev/dri/renderD128)")]
+    InvalidGpu(String),
+
+    #[error("GPU '{gpu}' not found in {}", .drm_path.display())]
+    GpuNotFound {
+        gpu: String,
+        drm_path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("No card found for render node {0}")]
+    NoCardForRenderNode(String),
+
+    /// --edid-file names a file that isn't there
+    #[error("EDID file {} does not exist", .0.display())]
+    EdidFileMissing(PathBuf),
+
+    #[error("Failed to read EDID file {}", .path.display())]
+    EdidRead {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// The connector's EDID is empty
+    #[error("{0} has no EDID (the display didn't provide one)")]
+    NoEdid(String),
+
+    /// --dump-edid couldn't write the EDID or edid-decode's text
+    #[error("Failed to write {}", .path.display())]
+    EdidDump {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Launcher command is empty")]
+    EmptyLauncher,
+
+    /// The --launcher program couldn't be started
+    #[error("Failed to run launcher: {command}")]
+    LauncherSpawn {
+        command: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Launcher output is not valid UTF-8")]
+    LauncherOutput(#[source] FromUtf8Error),
+
+    /// The launcher printed something that isn't one of the rows
+    #[error("Could not match selection '{0}' to a display")]
+    UnknownSelection(String),
+
+    /// The gamescope binary couldn't be started
+    #[error("Failed to launch gamescope in {mode} mode")]
+    GamescopeSpawn {
+        mode: LaunchMode,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Gamescope exited with {status} in {mode} mode")]
+    GamescopeExited { mode: LaunchMode, status: ExitStatus },
+
+    /// Gamescope kept crashing on startup, and the user declined the safer
+    /// settings or those failed too
+    #[error("Gamescope failed to start{}", fallback_note(.fallbacks_tried))]
+    GamescopeStartFailed { fallbacks_tried: bool },
+}
+
+fn fallback_note(fallbacks_tried: &bool) -> &'static str {
+    if *fallbacks_tried {
+        ", even with all fallbacks applied"
+    } else {
+        ""
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,321 @@
+use crate::cli::{Args, ScalingFilter, ScalingMode};
+use crate::display::{parse_resolution, DisplayInfo};
+use crate::edid::DisplayCapabilities;
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+
+/// How gamescope was launched, for errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchMode {
+    /// On the display itself, through DRM
+    Native,
+    /// In a window inside the current desktop
+    Nested,
+    /// On a virtual output, for streaming
+    Headless,
+    /// With no display detected, at 1920x1080
+    Fallback,
+}
+
+impl fmt::Display for LaunchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LaunchMode::Native => "native",
+            LaunchMode::Nested => "nested",
+            LaunchMode::Headless => "headless",
+            LaunchMode::Fallback => "fallback",
+        })
+    }
+}
+
+/// Gamescope args added by --steam-deck-mode, matching SteamOS's own session:
+/// Xwayland for Steam plus one for games, touch input as direct touch,
+/// cursor hiding after 3s, upscaling capped at 2x and fades on
+/// app switch. They go before the user's extra args so those still win.
+pub const STEAM_DECK_GAMESCOPE_ARGS: &[&str] = &[
+    "--xwayland-count", "2",
+    "--default-touch-mode", "4",
+    "--hide-cursor-delay", "3000",
+    "--max-scale", "2",
+    "--fade-out-duration", "200",
+];
+
+pub fn build_gamescope_args(display: &DisplayInfo, caps: &DisplayCapabilities, args: &Args) -> Vec<String> {
+    let mut gs_args = vec![
+        "-W".to_string(), display.width.to_string(),
+        "-H".to_string(), display.height.to_string(),
+        "-r".to_string(), caps.max_refresh_rate.to_string(),
+    ];
+
+    // Render below the output resolution and let gamescope upscale; skipped if
+    // a fallback dropped the output below it
+    if let Some(res) = &args.render_resolution {
+        if let Ok((width, height)) = parse_resolution(res) {
+            if width <= display.width && height <= display.height {
+                gs_args.extend(["-w".to_string(), width.to_string(), "-h".to_string(), height.to_string()]);
+            }
+        }
+    }
+
+    // Specify which output to use (strip "cardX-" prefix unless another GPU
+    // has an output of the same name)
+    let output_name = if display.output_ambiguous {
+        display.connector_name.clone()
+    } else {
+        display.output_name().to_string()
+    };
+    gs_args.extend(["--prefer-output".to_string(), output_name]);
+
+    // With the name shared between GPUs or a --gpu given, also point gamescope
+    // at the right GPU, unless the user already chose one
+    if (display.output_ambiguous || args.gpu.is_some()) && !args.extra_args.iter().any(|arg| arg == "--prefer-vk-device") {
+        if let Some(gpu_id) = &display.gpu_id {
+            gs_args.extend(["--prefer-vk-device".to_string(), gpu_id.clone()]);
+        }
+    }
+
+    if caps.vrr {
+        gs_args.push("--adaptive-sync".to_string());
+
+        // Let Steam's refresh rate slider cover the whole VRR range
+        if let (Some(min), Some(max)) = (caps.vrr_min, caps.vrr_max) {
+            gs_args.extend(["--custom-refresh-rates".to_string(), format!("{}-{}", min, max)]);
+        }
+    }
+
+    if caps.hdr {
+        gs_args.extend(["--hdr-enabled".to_string(), "--hdr-itm-enable".to_string()]);
+    }
+
+    gs_args.extend(orientation_args(args));
+
+    gs_args.extend(scaling_args(args));
+
+    gs_args.extend(mangoapp_args(args));
+
+    gs_args.extend(toggle_args(args));
+
+    // Fullscreen (unless --windowed) and Steam integration
+    gs_args.extend(window_mode_args(args));
+    gs_args.extend(steam_integration_args(args));
+
+    if args.steam_deck_mode {
+        gs_args.extend(STEAM_DECK_GAMESCOPE_ARGS.iter().map(|arg| arg.to_string()));
+    }
+
+    // Add any extra user-provided args
+    gs_args.extend(args.extra_args.clone());
+
+    gs_args
+}
+
+/// Build the full `gamescope ... -- steam -bigpicture` (or --command) command,
+/// or just `gamescope ...` with --no-steam
+pub fn gamescope_command(display: &DisplayInfo, caps: &DisplayCapabilities, args: &Args) -> Command {
+    let gs_args = build_gamescope_args(display, caps, args);
+
+    let gamescope_bin = args.gamescope_bin.as_deref()
+        .unwrap_or(Path::new("gamescope"));
+
+    let mut cmd = Command::new(gamescope_bin);
+    cmd.args(&gs_args);
+    append_session_command(&mut cmd, args);
+
+    cmd
+}
+
+/// `--force-orientation` for --rotate, in gamescope's names for the
+/// kernel's panel orientations ("left" is Left Side Up, 90 degrees)
+pub fn orientation_args(args: &Args) -> Vec<String> {
+    let orientation = match args.rotate {
+        Some(0) => "normal",
+        Some(90) => "left",
+        Some(180) => "upsidedown",
+        Some(270) => "right",
+        _ => return Vec::new(),
+    };
+    vec!["--force-orientation".to_string(), orientation.to_string()]
+}
+
+/// Append `-- <program>` for gamescope to run: the --command if one was
+/// given, `true` with --test-launch, Steam Big Picture (with
+/// --bigpicture-flag, or the --steam-appid game) otherwise, or nothing with
+/// --no-steam. Also sets the --env/`[env]` variables, which gamescope passes
+/// on to the program.
+pub fn append_session_command(cmd: &mut Command, args: &Args) {
+    cmd.envs(args.env.iter().map(|(key, value)| (key, value)));
+
+    if let Some((program, program_args)) = args.command.split_first() {
+        cmd.arg("--").arg(program).args(program_args);
+    } else if args.test_launch {
+        // Exits straight away, and gamescope with it
+        cmd.arg("--").arg("true");
+    } else if !args.no_steam {
+        let steam_bin = args.steam_bin.as_deref()
+            .unwrap_or(Path::new("steam"));
+        cmd.arg("--").arg(steam_bin);
+        match args.steam_appid {
+            Some(appid) => {
+                cmd.arg("-applaunch").arg(appid.to_string());
+            }
+            None if !args.bigpicture_flag.is_empty() => {
+                cmd.arg(&args.bigpicture_flag);
+            }
+            None => {}
+        }
+        cmd.args(&args.steam_args);
+    }
+}
+
+/// Bits per color to request for the output: --bpc capped at the detected
+/// `max_bpc` unless --force-bpc, or 10 for HDR displays that support it,
+/// and never above --max-bpc. gamescope has no option for this, so it's set
+/// on the connector before launch (`drm::set_max_bpc`).
+pub fn output_bpc(caps: &DisplayCapabilities, args: &Args) -> Option<u32> {
+    let bpc = match args.bpc {
+        Some(bpc) if args.force_bpc => Some(bpc),
+        Some(bpc) => Some(bpc.min(caps.max_bpc)),
+        // Only ever raise the driver's default on our own
+        None => (caps.hdr && caps.max_bpc >= 10).then_some(10),
+    };
+    bpc.map(|bpc| args.max_bpc.map_or(bpc, |max| bpc.min(max)))
+}
+
+/// `--mangoapp` for the MangoHud overlay, unless --no-mangoapp
+pub fn mangoapp_args(args: &Args) -> Vec<String> {
+    if args.no_mangoapp {
+        Vec::new()
+    } else {
+        vec!["--mangoapp".to_string()]
+    }
+}
+
+/// `-e` (gamescope's `--steam`) for its Steam integration, which Big Picture
+/// relies on, unless --no-expose-wayland. It has nothing to do with the
+/// Wayland socket, which gamescope always creates.
+pub fn steam_integration_args(args: &Args) -> Vec<String> {
+    if args.no_expose_wayland {
+        Vec::new()
+    } else {
+        vec!["-e".to_string()]
+    }
+}
+
+/// `--force-grab-cursor`, `--immediate-flips` and `--force-composition` for
+/// --grab-cursor, --immediate-flips and --force-composition, leaving out any
+/// that are already among the extra args
+pub fn toggle_args(args: &Args) -> Vec<String> {
+    [
+        (args.grab_cursor, "--force-grab-cursor"),
+        (args.immediate_flips, "--immediate-flips"),
+        (args.force_composition, "--force-composition"),
+    ]
+    .into_iter()
+    .filter(|&(enabled, flag)| enabled && !args.extra_args.iter().any(|arg| arg == flag))
+    .map(|(_, flag)| flag.to_string())
+    .collect()
+}
+
+/// `--scaler`/`--filter` for --scaling and --filter, if given
+pub fn scaling_args(args: &Args) -> Vec<String> {
+    let mut gs_args = Vec::new();
+
+    if let Some(mode) = args.scaling {
+        let scaler = match mode {
+            ScalingMode::Fit => "fit",
+            ScalingMode::Fill => "fill",
+            ScalingMode::Stretch => "stretch",
+            ScalingMode::Integer => "integer",
+        };
+        gs_args.extend(["--scaler".to_string(), scaler.to_string()]);
+    }
+
+    if let Some(filter) = args.filter {
+        let filter = match filter {
+            ScalingFilter::Linear => "linear",
+            ScalingFilter::Nearest => "nearest",
+            ScalingFilter::Fsr => "fsr",
+            ScalingFilter::Nis => "nis",
+        };
+        gs_args.extend(["--filter".to_string(), filter.to_string()]);
+    }
+
+    gs_args
+}
+
+/// `-f` for fullscreen, or with --windowed nothing, or `-b` if also --borderless
+pub fn window_mode_args(args: &Args) -> Vec<String> {
+    if !args.windowed {
+        vec!["-f".to_string()]
+    } else if args.borderless {
+        vec!["-b".to_string()]
+    } else {
+        Vec::new()
+    }
+}
+
+/// The rate to hand gamescope: it parses -r and --nested-refresh as whole Hz,
+/// so a fractional rate like 59.94 goes to it as 60
+pub fn whole_hz(refresh_rate: f32) -> u32 {
+    refresh_rate.round() as u32
+}
+
+/// Args for a virtual output with no physical display behind it (--headless,
+/// or streaming to a Sunshine client with no monitor connected):
+/// `--backend headless -W <w> -H <h> -r <hz> -e --mangoapp` (without `-e`
+/// or `--mangoapp` if turned off), then the HDR
+/// args when `hdr` is set, any --scaling/--filter and toggle args, and the
+/// extra args. There's no `--prefer-output` since there is no connector to
+/// prefer.
+pub fn build_virtual_output_args(width: u32, height: u32, refresh_rate: f32, hdr: bool, args: &Args) -> Vec<String> {
+    let mut gs_args = vec![
+        "--backend".to_string(), "headless".to_string(),
+        "-W".to_string(), width.to_string(),
+        "-H".to_string(), height.to_string(),
+        "-r".to_string(), whole_hz(refresh_rate).to_string(),
+    ];
+    gs_args.extend(steam_integration_args(args));
+    gs_args.extend(mangoapp_args(args));
+    if hdr {
+        gs_args.extend(["--hdr-enabled".to_string(), "--hdr-itm-enable".to_string()]);
+    }
+    gs_args.extend(scaling_args(args));
+    gs_args.extend(toggle_args(args));
+
+    // Add any extra user-provided args
+    gs_args.extend(args.extra_args.clone());
+
+    gs_args
+}
+
+/// Args for gamescope in a window inside the current session (nested mode):
+/// the size for both gamescope and its window, then the same `-e`, scaling,
+/// mangoapp, toggle and extra args as a native launch. It's always
+/// windowed, borderless with --borderless.
+pub fn build_nested_args(width: u32, height: u32, refresh_rate: u32, args: &Args) -> Vec<String> {
+    let mut gs_args = vec![
+        "-W".to_string(), width.to_string(),
+        "-H".to_string(), height.to_string(),
+        "-r".to_string(), refresh_rate.to_string(),
+        "--nested-width".to_string(), width.to_string(),
+        "--nested-height".to_string(), height.to_string(),
+        "--nested-refresh".to_string(), refresh_rate.to_string(),
+    ];
+    gs_args.extend(steam_integration_args(args));
+
+    if args.borderless {
+        gs_args.push("-b".to_string());
+    }
+
+    gs_args.extend(scaling_args(args));
+
+    gs_args.extend(mangoapp_args(args));
+
+    gs_args.extend(toggle_args(args));
+
+    // Add any extra user-provided args
+    gs_args.extend(args.extra_args.clone());
+
+    gs_args
+}
@@ -0,0 +1,73 @@
+//! --pre-launch and --post-launch: shell commands run around each gamescope
+//! session, e.g. to switch on LED strips or set a fan curve and restore it
+
+use anyhow::{Context, Result};
+use std::process::Command;
+
+use crate::runner::CommandRunner;
+
+/// What the hooks get to know about the session, as environment variables
+#[derive(Debug, Clone)]
+pub struct HookContext {
+    /// `None` for nested, headless and fallback sessions
+    pub connector: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: u32,
+}
+
+impl HookContext {
+    /// `CONSOLE_MODE_CONNECTOR` (only with a display), `CONSOLE_MODE_RESOLUTION`,
+    /// `CONSOLE_MODE_WIDTH`, `CONSOLE_MODE_HEIGHT` and `CONSOLE_MODE_REFRESH_RATE`
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = Vec::new();
+        if let Some(ref connector) = self.connector {
+            env.push(("CONSOLE_MODE_CONNECTOR", connector.clone()));
+        }
+        env.extend([
+            ("CONSOLE_MODE_RESOLUTION", format!("{}x{}", self.width, self.height)),
+            ("CONSOLE_MODE_WIDTH", self.width.to_string()),
+            ("CONSOLE_MODE_HEIGHT", self.height.to_string()),
+            ("CONSOLE_MODE_REFRESH_RATE", self.refresh_rate.to_string()),
+        ]);
+        env
+    }
+}
+
+/// Run a hook through `sh -c` and wait for it; a non-zero exit is an error
+pub fn run_hook(runner: &dyn CommandRunner, command: &str, env: &[(&str, String)]) -> Result<()> {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd.envs(env.iter().map(|(key, value)| (key, value)));
+
+    let status = runner.status(&mut cmd)
+        .context(format!("Failed to run '{}'", command))?;
+    if !status.success() {
+        anyhow::bail!("'{}' exited with {}", command, status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::MockRunner;
+
+    #[test]
+    fn hooks_run_through_sh_and_fail_on_non_zero_exit() {
+        let runner = MockRunner::new();
+        run_hook(&runner, "ledctl on", &[]).unwrap();
+        assert_eq!(runner.calls(), [["sh", "-c", "ledctl on"]]);
+
+        let runner = MockRunner::new().with_output("sh", 1, "");
+        assert!(run_hook(&runner, "false", &[]).is_err());
+    }
+
+    #[test]
+    fn context_leaves_out_a_missing_connector() {
+        let context = HookContext { connector: None, width: 1920, height: 1080, refresh_rate: 60 };
+        let env = context.env();
+        assert!(!env.iter().any(|(key, _)| *key == "CONSOLE_MODE_CONNECTOR"));
+        assert!(env.contains(&("CONSOLE_MODE_RESOLUTION", "1920x1080".to_string())));
+    }
+}
@@ -0,0 +1,224 @@
+//! External display pickers for --launcher
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::debug_log;
+use crate::display::DisplayInfo;
+use crate::error::{Error, Result};
+use crate::runner::CommandRunner;
+
+/// A program that lets the user pick one of the displays
+pub trait Launcher {
+    /// The picked display, or `None` if the picker was closed without a
+    /// selection; failing to run it is an error
+    fn choose(&self, displays: &[DisplayInfo]) -> Result<Option<DisplayInfo>>;
+}
+
+/// The launcher for a --launcher command, picked by its program name
+pub fn launcher_for<'a>(runner: &'a dyn CommandRunner, launcher_cmd: &str, plain: bool) -> Result<Box<dyn Launcher + 'a>> {
+    let parts: Vec<&str> = launcher_cmd.split_whitespace().collect();
+    let Some((program, args)) = parts.split_first() else {
+        return Err(Error::EmptyLauncher);
+    };
+
+    // Everything supported so far reads rows on stdin and prints the pick,
+    // which fzf and skim do as well
+    Ok(Box::new(DmenuLauncher {
+        runner,
+        command: launcher_cmd.to_string(),
+        program: program.to_string(),
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        style: RowStyle::for_program(program, plain),
+    }))
+}
+
+/// dmenu-style pickers (dmenu, rofi -dmenu, wofi --dmenu, fzf, sk): rows
+/// go to stdin, the selected row comes back on stdout
+pub struct DmenuLauncher<'a> {
+    runner: &'a dyn CommandRunner,
+    /// The whole --launcher value, for errors
+    command: String,
+    program: String,
+    args: Vec<String>,
+    style: RowStyle,
+}
+
+/// Launchers whose rows can carry more than plain text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowStyle {
+    Plain,
+    /// Pango markup, plus an icon and the resolution as search meta per row
+    Rofi,
+    /// Pango markup only, since wofi wants image paths rather than icon names
+    Wofi,
+}
+
+impl RowStyle {
+    fn for_program(program: &str, plain: bool) -> Self {
+        match Path::new(program).file_name().and_then(|name| name.to_str()) {
+            _ if plain => RowStyle::Plain,
+            Some("rofi") => RowStyle::Rofi,
+            Some("wofi") => RowStyle::Wofi,
+            _ => RowStyle::Plain,
+        }
+    }
+
+    /// Flags that make the launcher render the markup and icons, if not already given
+    fn extra_args(self) -> &'static [&'static str] {
+        match self {
+            RowStyle::Plain => &[],
+            RowStyle::Rofi => &["-markup-rows", "-show-icons"],
+            RowStyle::Wofi => &["--allow-markup"],
+        }
+    }
+
+    fn format_option(self, number: usize, display: &DisplayInfo) -> String {
+        match self {
+            RowStyle::Plain => format!("[{}] {} - {}", number, display.connector_name, display.resolution),
+            RowStyle::Wofi => format!("[{}] <b>{}</b> - {}",
+                number, escape_markup(&display.connector_name), display.resolution),
+            RowStyle::Rofi => format!("[{}] <b>{}</b> - {}\0icon\x1fvideo-display\x1fmeta\x1f{}",
+                number, escape_markup(&display.connector_name), display.resolution, display.resolution),
+        }
+    }
+}
+
+impl Launcher for DmenuLauncher<'_> {
+    fn choose(&self, displays: &[DisplayInfo]) -> Result<Option<DisplayInfo>> {
+        let mut args = self.args.clone();
+        for &arg in self.style.extra_args() {
+            if !args.iter().any(|given| given == arg) {
+                args.push(arg.to_string());
+            }
+        }
+
+        // Create list of display options, numbered like the interactive menu so
+        // the selection maps back by index rather than by display name
+        let options: Vec<String> = displays
+            .iter()
+            .enumerate()
+            .map(|(i, d)| self.style.format_option(i + 1, d))
+            .collect();
+        let options_text = options.join("\n");
+
+        // What the launcher prints back: the row text without icon/meta, and
+        // without markup in case the launcher strips it
+        let plain_options: Vec<String> = options
+            .iter()
+            .map(|option| strip_markup(option.split('\0').next().unwrap_or_default()))
+            .collect();
+
+        // Options go to the launcher's stdin, the selection comes back on stdout
+        let output = self.runner.output(Command::new(&self.program).args(args), options_text.as_bytes())
+            .map_err(|source| Error::LauncherSpawn { command: self.command.clone(), source })?;
+
+        // dmenu and friends exit with 1 on Esc (fzf with 130); being killed
+        // counts as a cancel too
+        if !output.status.success() {
+            debug_log(&format!("Launcher exited with {}, treating as cancelled", output.status));
+            return Ok(None);
+        }
+
+        let selection = String::from_utf8(output.stdout)
+            .map_err(Error::LauncherOutput)?
+            .trim()
+            .to_string();
+
+        if selection.is_empty() {
+            return Ok(None);
+        }
+
+        // Prefer an exact line match, otherwise fall back to the "[N]" prefix in
+        // case the launcher reformatted the line
+        let selection = strip_markup(&selection);
+        let index = plain_options
+            .iter()
+            .position(|option| *option == selection)
+            .or_else(|| parse_option_index(&selection).filter(|&i| i < displays.len()))
+            .ok_or_else(|| Error::UnknownSelection(selection.clone()))?;
+
+        Ok(Some(displays[index].clone()))
+    }
+}
+
+/// Escape text for use in a Pango markup row
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Remove Pango tags and entities from a launcher row
+fn strip_markup(text: &str) -> String {
+    let mut plain = String::new();
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    plain.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
+
+/// Parse the zero-based index from a "[N] ..." launcher option
+fn parse_option_index(selection: &str) -> Option<usize> {
+    let (number, _) = selection.trim_start().strip_prefix('[')?.split_once(']')?;
+    number.trim().parse::<usize>().ok()?.checked_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::MockRunner;
+    use std::path::PathBuf;
+
+    fn display(name: &str, resolution: &str) -> DisplayInfo {
+        DisplayInfo {
+            connector_name: name.to_string(),
+            connector_path: PathBuf::from("/nonexistent"),
+            resolution: resolution.to_string(),
+            width: 1920,
+            height: 1080,
+            available_modes: Vec::new(),
+            output_ambiguous: false,
+            gpu_id: None,
+            modes_unavailable: false,
+            model: None,
+            serial: None,
+            native_mode: None,
+        }
+    }
+
+    #[test]
+    fn rofi_rows_map_back_to_displays() {
+        let displays = [display("card1-DP-1", "2560x1440"), display("card1-HDMI-A-1", "3840x2160")];
+        let runner = MockRunner::new().with_output("rofi", 0, "[2] card1-HDMI-A-1 - 3840x2160\n");
+        let launcher = launcher_for(&runner, "rofi -dmenu", false).unwrap();
+
+        let picked = launcher.choose(&displays).unwrap().unwrap();
+        assert_eq!(picked.connector_name, "card1-HDMI-A-1");
+        assert_eq!(runner.calls(), [["rofi", "-dmenu", "-markup-rows", "-show-icons"]]);
+    }
+
+    #[test]
+    fn closing_the_launcher_is_a_cancel() {
+        let displays = [display("card1-DP-1", "2560x1440")];
+        let runner = MockRunner::new().with_output("fzf", 130, "");
+        assert!(launcher_for(&runner, "fzf", false).unwrap().choose(&displays).unwrap().is_none());
+        assert!(matches!(launcher_for(&runner, "  ", false), Err(Error::EmptyLauncher)));
+
+        let runner = MockRunner::new().with_output("fzf", 0, "something else\n");
+        let result = launcher_for(&runner, "fzf", false).unwrap().choose(&displays);
+        assert!(matches!(result, Err(Error::UnknownSelection(selection)) if selection == "something else"));
+    }
+
+    #[test]
+    fn markup_is_stripped_and_option_numbers_parsed() {
+        assert_eq!(strip_markup(&format!("<b>{}</b>", escape_markup("a<b>&c"))), "a<b>&c");
+        assert_eq!(parse_option_index("[3] card1-DP-1"), Some(2));
+        assert_eq!(parse_option_index("[0] card1-DP-1"), None);
+        assert_eq!(parse_option_index("card1-DP-1"), None);
+    }
+}
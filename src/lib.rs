@@ -0,0 +1,30 @@
+//! Display detection, EDID parsing and gamescope argument building behind
+//! the `console-mode` launcher
+
+pub mod cache;
+#[cfg(feature = "cec")]
+pub mod cec;
+pub mod cli;
+pub mod config;
+pub mod controller;
+#[cfg(feature = "ddc")]
+pub mod ddc;
+pub mod display;
+pub mod drm;
+pub mod edid;
+pub mod error;
+pub mod gamescope;
+pub mod hooks;
+pub mod launcher;
+pub mod logging;
+pub mod probe;
+pub mod runner;
+pub mod session;
+pub mod signals;
+pub mod state;
+#[cfg(feature = "status-server")]
+pub mod status;
+pub mod theme;
+
+pub use error::Error;
+pub use logging::debug_log;
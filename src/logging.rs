@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::state;
+
+/// Where debug messages go; unset means logging is off
+enum Sink {
+    Stderr,
+    File(Mutex<File>),
+}
+
+static SINK: OnceLock<Sink> = OnceLock::new();
+
+/// Set up debug logging from the --verbose, --log-file and
+/// --capture-gamescope flags
+///
+/// Logging is off unless one of them is given. With --verbose alone,
+/// messages go to stderr, except in TUI modes (which own the terminal)
+/// where they go to `$XDG_STATE_HOME/console-mode/debug.log` instead.
+/// --capture-gamescope without either of the others also logs to that file,
+/// so gamescope's output is kept somewhere.
+pub fn init(verbose: bool, log_file: Option<&Path>, tui: bool, capture_gamescope: bool) -> Result<()> {
+    let path = match log_file {
+        Some(path) => path.to_path_buf(),
+        None if !verbose && !capture_gamescope => return Ok(()),
+        None if verbose && !tui => {
+            let _ = SINK.set(Sink::Stderr);
+            return Ok(());
+        }
+        None => default_log_path().context("Could not determine state directory for the log file")?,
+    };
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .context(format!("Failed to create log directory {}", dir.display()))?;
+    }
+
+    // Logs include device names and environment details, keep them private
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o600)
+        .open(&path)
+        .context(format!("Failed to open log file {}", path.display()))?;
+
+    let _ = SINK.set(Sink::File(Mutex::new(file)));
+    Ok(())
+}
+
+/// Whether debug messages are going to stderr
+pub fn logs_to_stderr() -> bool {
+    matches!(SINK.get(), Some(Sink::Stderr))
+}
+
+/// Log a debug message, if logging was enabled with [`init`]
+pub fn debug_log(msg: &str) {
+    let line = format!("[{}] {}", chrono::Local::now().format("%H:%M:%S%.3f"), msg);
+    match SINK.get() {
+        Some(Sink::Stderr) => eprintln!("{}", line),
+        Some(Sink::File(file)) => {
+            if let Ok(mut file) = file.lock() {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+        None => {}
+    }
+}
+
+fn default_log_path() -> Option<PathBuf> {
+    Some(state::state_dir()?.join("debug.log"))
+}
@@ -1,207 +1,551 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{CommandFactory, Parser};
+use console_mode::cli::{parse_refresh_rate, AfterLaunch, Args, ColorFormat, OnCancel};
+use console_mode::config::Config;
+use console_mode::controller::{button_map, ButtonAction};
+use console_mode::debug_log;
+use console_mode::display::{closest_mode, common_mode, detect_displays, find_display, is_internal_panel, parse_mode, parse_resolution, resolve_gpu, DisplayInfo, DRM_PATH};
+use console_mode::drm::{drm_master_held, panel_orientation, set_max_bpc, set_ycbcr420, ycbcr420_forced};
+use console_mode::edid::DisplayCapabilities;
+use console_mode::Error;
+use console_mode::hooks::{run_hook, HookContext};
+use console_mode::gamescope::{append_session_command, build_gamescope_args, build_nested_args, build_virtual_output_args, steam_integration_args, gamescope_command, output_bpc, toggle_args, whole_hz, window_mode_args, LaunchMode};
+use console_mode::launcher::launcher_for;
+use console_mode::logging;
+use console_mode::probe::{dump_edid, hdmi_pixel_format, probe_capabilities, time_detection};
+use console_mode::runner::{CommandRunner, SystemRunner};
+use console_mode::session::{self, shell_quote, SessionFiles};
+use console_mode::signals;
+use console_mode::state;
+use console_mode::theme::{build_theme, Theme};
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
-use evdev::{Device, InputEventKind, Key};
+use evdev::{Device, FFEffect, FFEffectData, FFEffectKind, FFEffectType, FFReplay, FFTrigger, InputEventKind, Key};
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Layout, Rect},
+    layout::{Alignment, Constraint, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
     Frame, Terminal,
 };
-use regex::Regex;
-use std::fs::{self, OpenOptions};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
-use std::sync::mpsc;
+use std::process::Command;
+use std::sync::{mpsc, Mutex, Once, OnceLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Suppress progress banners and capability printouts (--quiet)
+static QUIET: OnceLock<bool> = OnceLock::new();
+
+/// `println!` for decorative progress output, left out with --quiet.
+/// Prompts, requested output (--dry-run commands) and errors use
+/// `println!`/`eprintln!` directly.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if !QUIET.get().copied().unwrap_or(false) {
+            println!($($arg)*);
+        }
+    };
+}
 
-/// Log debug messages to a file (since TUI takes over the terminal)
-fn debug_log(msg: &str) {
-    if let Ok(mut file) = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("/tmp/console-mode-debug.log")
-    {
-        let _ = writeln!(file, "[{}] {}", chrono::Local::now().format("%H:%M:%S%.3f"), msg);
+fn main() -> Result<()> {
+    let mut args = Args::parse();
+
+    if let Some(shell) = args.generate_completions {
+        clap_complete::generate(shell, &mut Args::command(), "console-mode", &mut io::stdout());
+        return Ok(());
     }
-}
 
-/// Console Mode - A gamescope session launcher with automatic display detection
-#[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
-struct Args {
-    /// Override display selection (connector name, e.g., "card1-HDMI-A-1")
-    #[arg(short, long)]
-    display: Option<String>,
+    // Take gamescope down with us when stopped
+    signals::install();
 
-    /// Override resolution (e.g., "1920x1080")
-    #[arg(short, long)]
-    resolution: Option<String>,
+    split_resolution_refresh(&mut args)?;
 
-    /// Override refresh rate in Hz
-    #[arg(short = 'f', long)]
-    refresh_rate: Option<u32>,
+    let _ = DELAY_SCALE.set(if args.no_delays { 0.0 } else { args.delay_scale });
+    let _ = QUIET.set(args.quiet);
 
-    /// Force enable VRR/Adaptive Sync
-    #[arg(long)]
-    force_vrr: bool,
+    // The TUI modes own the terminal, so they can only log to a file
+    logging::init(args.verbose, args.log_file.as_deref(), args.tui_launcher || args.idle_watcher, args.capture_gamescope)?;
 
-    /// Force enable HDR
-    #[arg(long)]
-    force_hdr: bool,
+    if args.install_session {
+        return install_session(&args);
+    }
 
-    /// Disable VRR even if supported
-    #[arg(long)]
-    no_vrr: bool,
+    // Check for Sunshine client environment variables as fallback
+    apply_sunshine_env_fallbacks(&mut args);
 
-    /// Disable HDR even if supported
-    #[arg(long)]
-    no_hdr: bool,
+    // Set up environment variables
+    setup_environment(&mut args)?;
 
-    /// Use safe mode (disable advanced features)
-    #[arg(long)]
-    safe_mode: bool,
+    if args.steam_deck_mode {
+        apply_steam_deck_mode(&mut args);
+    }
 
-    /// Custom gamescope binary path
-    #[arg(long)]
-    gamescope_bin: Option<PathBuf>,
+    // Load per-display and named profiles
+    let config = Config::load(args.config.as_deref())?;
+    config.apply_env(&mut args);
+    if config.mangoapp == Some(false) {
+        args.no_mangoapp = true;
+    }
+    config.apply_hooks(&mut args);
+    if !args.no_mangoapp && find_executable(Path::new("mangoapp")).is_none() {
+        debug_log("mangoapp not found in PATH, running without --mangoapp");
+        args.no_mangoapp = true;
+    }
 
-    /// Custom steam binary path
-    #[arg(long)]
-    steam_bin: Option<PathBuf>,
+    let theme_name = args.theme.or(config.theme.name).unwrap_or_default();
+    let _ = THEME.set(build_theme(theme_name, &config.theme.colors).context("Invalid [theme] config")?);
 
-    /// Additional steam arguments
-    #[arg(long, value_delimiter = ' ', num_args = 1..)]
-    steam_args: Vec<String>,
+    if args.list_profiles {
+        list_profiles(&config);
+        return Ok(());
+    }
 
-    /// Launcher command for display selection (e.g., "dmenu", "rofi -dmenu", "wofi --dmenu")
-    #[arg(long)]
-    launcher: Option<String>,
+    if let Some(ref name) = args.profile {
+        let profile = config.named_profile(name)?;
+        info!("Using profile {}", name);
+        profile.apply(&mut args);
+        split_resolution_refresh(&mut args)?;
+    }
 
-    /// Launch TUI monitor selector with controller support
-    #[arg(long)]
-    tui_launcher: bool,
+    // -e is gamescope's Steam integration, so only sessions without Steam
+    // should go without it
+    if args.no_expose_wayland && args.command.is_empty() && !args.no_steam && !args.test_launch {
+        eprintln!("⚠ --no-expose-wayland leaves out gamescope's Steam integration (-e), which Steam Big Picture needs; use it with --command or --no-steam");
+    }
 
-    /// Start in idle watcher mode - waits for controller input or Sunshine session
-    #[arg(long)]
-    idle_watcher: bool,
+    if args.benchmark_detection {
+        return run_benchmark_detection(&args);
+    }
+
+    // Report capabilities instead of launching
+    if args.detect_only || args.refresh_list {
+        return run_detect_only(&args, &config);
+    }
+
+    if args.headless && args.after_launch == AfterLaunch::Menu {
+        anyhow::bail!("--after-launch menu needs displays to pick from; use --after-launch relaunch with --headless");
+    }
+
+    // Fail early rather than from deep inside the launch; a dry run only
+    // prints the command, so it works without them
+    if !args.dry_run {
+        check_binaries(&args)?;
+    }
+
+    if args.daemon {
+        return run_daemon(&args, &config);
+    }
+
+    // Keep starting sessions until the user quits instead of launching,
+    // or --after-launch says to stop. Ctrl+C outside the TUI kills us
+    // along with gamescope, and the TUIs treat it as quit.
+    let mut session_args = args.clone();
+    loop {
+        let Some(launched_args) = run_session(session_args, &config)? else {
+            return Ok(());
+        };
+        if args.dry_run {
+            return Ok(());
+        }
+        if args.test_launch {
+            match launched_args.display {
+                Some(ref display) => println!("✓ Test launch passed: gamescope started on {} and exited cleanly", display),
+                None => println!("✓ Test launch passed: gamescope started and exited cleanly"),
+            }
+            return Ok(());
+        }
 
-    /// Additional gamescope arguments
-    #[arg(last = true)]
-    extra_args: Vec<String>,
+        session_args = match args.after_launch {
+            AfterLaunch::Exit => return Ok(()),
+            AfterLaunch::Relaunch => {
+                info!("\nSession ended, relaunching (Ctrl+C to exit)...");
+                // Same display again, without asking
+                launched_args
+            }
+            AfterLaunch::Menu => {
+                info!("\nSession ended, returning to display selection...");
+                Args {
+                    tui_launcher: true,
+                    idle_watcher: false,
+                    ..args.clone()
+                }
+            }
+        };
+        pause(Duration::from_secs(2));
+    }
 }
 
-#[derive(Debug, Clone)]
-struct DisplayInfo {
-    connector_name: String,
-    connector_path: PathBuf,
+/// One display in the --detect-only --json report
+#[derive(Serialize)]
+struct DisplayReport {
+    connector: String,
     resolution: String,
-    width: u32,
-    height: u32,
+    /// EDID monitor name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(flatten)]
+    capabilities: DisplayCapabilities,
+    warnings: Vec<String>,
 }
 
-#[derive(Debug, Default)]
-struct DisplayCapabilities {
-    vrr: bool,
-    hdr: bool,
-    max_refresh_rate: u32,
-    max_bpc: u32,
+#[derive(Serialize)]
+struct DetectReport {
+    displays: Vec<DisplayReport>,
 }
 
-fn main() -> Result<()> {
-    let mut args = Args::parse();
+/// Print each named profile with its display, if it has one
+fn list_profiles(config: &Config) {
+    if config.profiles.is_empty() {
+        println!("No profiles configured (add [profiles.NAME] tables to the config)");
+        return;
+    }
 
-    // Check for Sunshine client environment variables as fallback
-    apply_sunshine_env_fallbacks(&mut args);
+    for (name, profile) in &config.profiles {
+        match profile.display {
+            Some(ref display) => println!("{} ({})", name, display),
+            None => println!("{}", name),
+        }
+    }
+}
 
-    // Set up environment variables
-    setup_environment()?;
+/// Write a display manager session that runs this binary with the other
+/// args given alongside --install-session
+fn install_session(args: &Args) -> Result<()> {
+    let binary = std::env::current_exe().context("Could not determine the console-mode binary path")?;
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    let files = SessionFiles::new(&args.prefix, &binary, &session::session_args(&argv));
+
+    if args.dry_run {
+        println!("# {}\n{}", files.wrapper_path.display(), files.wrapper);
+        println!("# {}\n{}", files.desktop_path.display(), files.desktop);
+        return Ok(());
+    }
+
+    files.install()?;
+    println!("Installed {}", files.wrapper_path.display());
+    println!("Installed {}", files.desktop_path.display());
+    println!("Pick \"Console Mode\" in your display manager's session menu");
+    Ok(())
+}
+
+/// Print the refresh rates the EDID lists, one line per resolution
+fn print_refresh_list(caps: &DisplayCapabilities) {
+    let rates = caps.refresh_rates();
+    if rates.is_empty() {
+        println!("No timings listed in the EDID");
+        return;
+    }
+    println!("Refresh rates:");
+    for ((width, height), refresh_rates) in rates {
+        let refresh_rates: Vec<String> = refresh_rates.iter().map(u32::to_string).collect();
+        println!("  {}x{}: {} Hz", width, height, refresh_rates.join(", "));
+    }
+}
+
+/// Print each connected display's capabilities (--detect-only) and/or the
+/// refresh rates its EDID lists (--refresh-list), as a launch would detect
+/// them (config profile and CLI overrides included), without launching
+fn run_detect_only(args: &Args, config: &Config) -> Result<()> {
+    let mut displays = connected_displays(args)?;
+    if let Some(ref name) = args.display {
+        displays = vec![find_display(&displays, name)?.clone()];
+    }
+    if let Some(ref path) = args.dump_edid {
+        match displays.as_slice() {
+            [display] => dump_display_edid(display, path),
+            [] => {}
+            _ => anyhow::bail!("--dump-edid writes one display's EDID; pick it with --display"),
+        }
+    }
+
+    let mut report = DetectReport { displays: Vec::new() };
+    for display in &displays {
+        let mut display_args = args.clone();
+        apply_display_profile(config, display, &mut display_args)?;
+
+        if args.json {
+            let (capabilities, warnings) = probe_capabilities(&SystemRunner, display, &display_args)?;
+            report.displays.push(DisplayReport {
+                connector: display.connector_name.clone(),
+                resolution: display.resolution.clone(),
+                model: display.model.clone(),
+                capabilities,
+                warnings,
+            });
+        } else {
+            match display.model {
+                Some(ref model) => println!("=== {} ({}, {}) ===\n", display.connector_name, display.resolution, model),
+                None => println!("=== {} ({}) ===\n", display.connector_name, display.resolution),
+            }
+            let caps = if args.detect_only {
+                detect_capabilities(&SystemRunner, display, &display_args)?
+            } else {
+                probe_capabilities(&SystemRunner, display, &display_args)?.0
+            };
+            if args.refresh_list {
+                print_refresh_list(&caps);
+            }
+            println!();
+        }
+    }
 
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report).context("Failed to serialize capabilities")?);
+    } else if displays.is_empty() {
+        println!("No connected displays detected");
+    }
+
+    Ok(())
+}
+
+/// Write the display's EDID for --dump-edid; a display without a readable
+/// EDID only gets a warning, since that's worth reporting too.
+/// Goes to stderr so --json output stays parseable.
+fn dump_display_edid(display: &DisplayInfo, path: &Path) {
+    match dump_edid(&SystemRunner, display, path) {
+        Ok(text_path) => {
+            eprintln!("Wrote {}'s EDID to {}", display.connector_name, path.display());
+            match text_path {
+                Some(text_path) => eprintln!("Wrote edid-decode output to {}", text_path.display()),
+                None => eprintln!("  (edid-decode didn't run, so only the raw EDID was written)"),
+            }
+        }
+        Err(e) => eprintln!("⚠ Could not dump EDID: {:#}", anyhow::Error::from(e)),
+    }
+}
+
+/// Print how long detection takes (--benchmark-detection), to see what
+/// startup spends its time on and what the capability cache saves
+fn run_benchmark_detection(args: &Args) -> Result<()> {
+    let ms = |duration: Duration| format!("{:.2} ms", duration.as_secs_f64() * 1000.0);
+
+    let started = Instant::now();
+    let displays = connected_displays(args)?;
+    println!("detect_displays: {} ({} connected)", ms(started.elapsed()), displays.len());
+
+    for display in &displays {
+        println!("\n{}:", display.connector_name);
+        let timings = match time_detection(&SystemRunner, display) {
+            Ok(timings) => timings,
+            Err(e) => {
+                println!("  {:#}", anyhow::Error::from(e));
+                continue;
+            }
+        };
+        println!("  read EDID:               {} ({} bytes)", ms(timings.read_edid), timings.edid_bytes);
+        println!("  cache lookup:            {} ({})", ms(timings.cache_lookup), if timings.cache_hit { "hit" } else { "miss" });
+        match timings.edid_decode {
+            Some(duration) => println!("  edid-decode:             {}", ms(duration)),
+            None => println!("  edid-decode:             could not run"),
+        }
+        match timings.parse_edid_decode {
+            Some(duration) => println!("  parse_edid_capabilities: {}", ms(duration)),
+            None => println!("  parse_edid_capabilities: skipped (no edid-decode output)"),
+        }
+        println!("  built-in parser:         {}", ms(timings.parse_builtin));
+    }
+
+    Ok(())
+}
+
+/// Detect connected displays, only those on the --gpu card if one was
+/// given, and only external or built-in ones with --external-only/--internal-only
+fn connected_displays(args: &Args) -> Result<Vec<DisplayInfo>> {
+    let mut displays = detect_displays(Path::new(DRM_PATH), args.sort_displays, args.include_virtual)?;
+    if let Some(ref card) = args.gpu {
+        displays.retain(|d| d.connector_name.split_once('-').is_some_and(|(c, _)| c == card));
+    }
+    if args.external_only || args.internal_only {
+        displays.retain(|d| {
+            let keep = is_internal_panel(d) == args.internal_only;
+            if !keep {
+                debug_log(&format!("{}: skipped by --{}-only", d.connector_name, if args.internal_only { "internal" } else { "external" }));
+            }
+            keep
+        });
+    }
+    Ok(displays)
+}
+
+/// Detect connected displays, polling for up to --wait-for-display seconds
+/// while there are none, so a TV that's still powering on isn't missed
+fn detect_displays_waiting(args: &Args) -> Result<Vec<DisplayInfo>> {
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    let mut displays = connected_displays(args)?;
+    let Some(secs) = args.wait_for_display.filter(|_| displays.is_empty()) else {
+        return Ok(displays);
+    };
+
+    info!("No displays connected yet, waiting up to {}s...", secs);
+    let deadline = Instant::now() + Duration::from_secs(secs);
+    while displays.is_empty() && Instant::now() < deadline {
+        thread::sleep(POLL_INTERVAL);
+        displays = connected_displays(args)?;
+    }
+
+    if !displays.is_empty() {
+        debug_log(&format!("Display appeared after waiting: {}", displays[0].connector_name));
+    }
+    Ok(displays)
+}
+
+/// Pick a display and run one gamescope session on it. Returns the args
+/// the session was launched with, or `None` if the user quit instead.
+fn run_session(mut args: Args, config: &Config) -> Result<Option<Args>> {
     // If TUI launcher mode is requested, run the TUI
     if args.tui_launcher {
-        return run_tui_launcher(args);
+        return run_tui_launcher(args, config);
     }
 
     // If idle watcher mode is requested, wait for input or Sunshine session
     if args.idle_watcher {
-        return run_idle_watcher(args);
+        return run_idle_watcher(args, config);
     }
 
-    // Check if we're running nested inside another compositor
-    let is_nested = is_running_nested();
+    // Streaming without a monitor needs no display detection at all
+    if args.headless {
+        launch_gamescope_headless(&args)?;
+        return Ok(Some(args));
+    }
 
-    if is_nested {
-        println!("Detected nested environment (running inside another compositor)");
-        println!("Launching in nested Wayland mode...");
-        println!("\nNote: You may see some warnings from gamescope/Mesa:");
-        println!("  - 'No CAP_SYS_NICE' - normal, doesn't affect gaming performance");
-        println!("  - 'libdecor warnings' - expected in nested mode");
-        println!("  - 'RADV not conformant' - safe to ignore, RADV works great for gaming");
-        println!("  - 'vk_khr_present_wait overridden' - informational only\n");
-        thread::sleep(Duration::from_secs(2));
-        return launch_gamescope_nested(&args);
+    // Check if we're running nested inside another compositor
+    if is_running_nested(&args) {
+        launch_nested_session(&args)?;
+        return Ok(Some(args));
     }
 
     // Detect connected displays
-    let displays = detect_displays()?;
+    let displays = detect_displays_waiting(&args)?;
+
+    // Streaming with no monitor: give the Sunshine client a virtual output
+    // rather than guessing at a physical one
+    let display_missing = args.display.as_ref()
+        .is_some_and(|name| find_display(&displays, name).is_err());
+    if (displays.is_empty() || display_missing) && sunshine_client_connected() {
+        info!("No matching display connected, streaming to the Sunshine client on a virtual output");
+        launch_gamescope_headless(&args)?;
+        return Ok(Some(args));
+    }
 
     if displays.is_empty() {
         eprintln!("⚠ No connected displays detected, using fallback: 1920x1080");
-        thread::sleep(Duration::from_secs(1));
-        return launch_gamescope_fallback(&args);
+        eprintln!("  (use --headless to stream without a monitor)");
+        pause(Duration::from_secs(1));
+        launch_gamescope_fallback(&args)?;
+        return Ok(Some(args));
     }
 
     // Select display
     let selected_display = if let Some(ref display_name) = args.display {
-        displays
-            .iter()
-            .find(|d| d.connector_name == *display_name)
-            .context(format!("Display '{}' not found", display_name))?
-            .clone()
+        find_display(&displays, display_name)?.clone()
+    } else if let Some(internal) = displays.iter().find(|d| args.steam_deck_mode && is_internal_panel(d)) {
+        info!("Steam Deck mode: using internal panel {} at {}", internal.connector_name, internal.resolution);
+        internal.clone()
+    } else if args.mirror {
+        // gamescope drives a single output; the others only limit the resolution
+        displays[0].clone()
     } else if displays.len() > 1 {
         if let Some(ref launcher_cmd) = args.launcher {
-            select_display_launcher(&displays, launcher_cmd)?
+            match launcher_for(&SystemRunner, launcher_cmd, args.launcher_plain)?.choose(&displays)? {
+                Some(display) => display,
+                None => match args.on_cancel {
+                    OnCancel::Abort => anyhow::bail!("Display selection cancelled"),
+                    OnCancel::First => {
+                        info!("Display selection cancelled, using {}", displays[0].connector_name);
+                        displays[0].clone()
+                    }
+                    OnCancel::Interactive => select_display_interactive(&displays, &args, config)?,
+                },
+            }
         } else {
-            select_display_interactive(&displays)?
+            select_display_interactive(&displays, &args, config)?
         }
     } else {
-        println!("Detected display: {} at {}", displays[0].connector_name, displays[0].resolution);
-        thread::sleep(Duration::from_secs(1));
+        info!("Detected display: {} at {}", displays[0].connector_name, displays[0].resolution);
+        pause(Duration::from_secs(1));
         displays[0].clone()
     };
 
-    // Override resolution if specified
-    let display = if let Some(ref res) = args.resolution {
-        let (width, height) = parse_resolution(res)?;
-        DisplayInfo {
-            resolution: res.clone(),
-            width,
-            height,
-            ..selected_display
+    if args.mirror && displays.len() > 1 {
+        match common_mode(&displays) {
+            Some((width, height)) => {
+                info!("Mirror: using {}x{}, supported by all {} displays", width, height, displays.len());
+                args.resolution = Some(format!("{}x{}", width, height));
+            }
+            None => eprintln!("⚠ Mirror: no resolution is supported by every display, using {}'s {}",
+                selected_display.connector_name, selected_display.resolution),
         }
-    } else {
-        selected_display
+    }
+
+    args.display = Some(selected_display.connector_name.clone());
+    launch_with_display(&selected_display, args.clone(), config)?;
+    Ok(Some(args))
+}
+
+/// Split a `WxH@HZ` resolution into the resolution and the refresh rate; an
+/// explicit --refresh-rate wins over the one in the mode
+fn split_resolution_refresh(args: &mut Args) -> Result<()> {
+    let Some(mode) = args.resolution.as_deref().filter(|res| res.contains('@')) else {
+        return Ok(());
     };
+    let (width, height, refresh_rate) = parse_mode(mode)?;
+    args.resolution = Some(format!("{}x{}", width, height));
+    if args.refresh_rate.is_none() {
+        args.refresh_rate = refresh_rate;
+    }
+    Ok(())
+}
+
+/// Apply the display's config profile, if it has one, underneath the CLI
+/// flags, splitting a `WxH@HZ` resolution from it as from --resolution.
+/// Every path that detects or launches with a profile goes through here.
+/// Returns whether there was a profile.
+fn apply_display_profile(config: &Config, display: &DisplayInfo, args: &mut Args) -> Result<bool> {
+    let profile = config.profile_for(&display.connector_name);
+    if let Some(profile) = profile {
+        profile.apply(args);
+        split_resolution_refresh(args)?;
+    }
+    Ok(profile.is_some())
+}
 
-    // Detect display capabilities
-    println!("\n=== Detecting Display Capabilities ===\n");
-    let capabilities = detect_capabilities(&display, &args)?;
-    println!();
-    thread::sleep(Duration::from_secs(2));
+/// TUI colors (--theme and the config's [theme])
+static THEME: OnceLock<Theme> = OnceLock::new();
 
-    // Launch gamescope
-    launch_gamescope(&display, &capabilities, &args)
+fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
 }
 
-fn setup_environment() -> Result<()> {
+/// Scale for the pauses that give time to read output (--delay-scale, --no-delays)
+static DELAY_SCALE: OnceLock<f32> = OnceLock::new();
+
+/// Pause so what was just printed can be read before the screen changes.
+/// Skipped with --quiet, where there's little to read. Waits that are
+/// needed for things to work use `thread::sleep` instead.
+fn pause(duration: Duration) {
+    if QUIET.get().copied().unwrap_or(false) {
+        return;
+    }
+    let scale = DELAY_SCALE.get().copied().unwrap_or(1.0);
+    if scale > 0.0 {
+        thread::sleep(duration.mul_f32(scale));
+    }
+}
+
+/// Session environment for gamescope and Steam. Also resolves --gpu to its
+/// card name, which is what display detection filters on afterwards.
+fn setup_environment(args: &mut Args) -> Result<()> {
     std::env::set_var("STEAM_FORCE_DESKTOPUI_SCALING", "1");
     std::env::set_var("XDG_SESSION_TYPE", "wayland");
     std::env::set_var("LIBSEAT_BACKEND", "logind");
@@ -212,14 +556,98 @@ fn setup_environment() -> Result<()> {
         std::env::set_var("XDG_RUNTIME_DIR", format!("/run/user/{}", uid));
     }
 
+    if let Some(ref requested) = args.gpu {
+        let gpu = resolve_gpu(Path::new(DRM_PATH), requested)?;
+        let device = Path::new("/dev/dri").join(&gpu.card);
+        if !device.exists() {
+            anyhow::bail!("GPU device {} does not exist", device.display());
+        }
+
+        info!("Using GPU {}", gpu.card);
+        std::env::set_var("WLR_DRM_DEVICES", &device);
+        // Mesa clients (the games) render on this GPU too
+        if let Some(ref dri_prime) = gpu.dri_prime {
+            std::env::set_var("DRI_PRIME", dri_prime);
+        }
+        args.gpu = Some(gpu.card);
+    }
+
+    Ok(())
+}
+
+/// Make sure gamescope and the program it runs (Steam or --command) exist
+fn check_binaries(args: &Args) -> Result<()> {
+    let gamescope_bin = args.gamescope_bin.as_deref()
+        .unwrap_or(Path::new("gamescope"));
+    if find_executable(gamescope_bin).is_none() {
+        anyhow::bail!("gamescope not found ({}); install your distribution's gamescope package, or point --gamescope-bin at it",
+            gamescope_bin.display());
+    }
+
+    if let Some(program) = args.command.first() {
+        if find_executable(Path::new(program)).is_none() {
+            anyhow::bail!("--command program '{}' not found", program);
+        }
+    } else if !args.no_steam && !args.test_launch {
+        let steam_bin = args.steam_bin.as_deref()
+            .unwrap_or(Path::new("steam"));
+        if find_executable(steam_bin).is_none() {
+            anyhow::bail!("Steam not found ({}); install your distribution's steam package, or point --steam-bin at it (e.g. a wrapper script for Flatpak Steam)",
+                steam_bin.display());
+        }
+    }
+
     Ok(())
 }
 
+/// Resolve a program the way `Command` does: a path is used as is, a bare
+/// name is looked up in PATH
+fn find_executable(program: &Path) -> Option<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let is_executable = |path: &Path| {
+        fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    };
+
+    if program.components().count() > 1 {
+        return is_executable(program).then(|| program.to_path_buf());
+    }
+
+    let path = std::env::var_os("PATH")?;
+    let found = std::env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate));
+    debug_log(&format!("Looking up {} in PATH: {:?}", program.display(), found));
+    found
+}
+
+/// Environment and Steam args for --steam-deck-mode; the gamescope side is
+/// handled in `build_gamescope_args`
+fn apply_steam_deck_mode(args: &mut Args) {
+    // Makes Steam treat the session as a Steam Deck (Deck UI, performance overlay)
+    std::env::set_var("SteamDeck", "1");
+    std::env::set_var("STEAM_USE_MANGOAPP", "1");
+    std::env::set_var("STEAM_MANGOAPP_PRESETS_SUPPORTED", "1");
+
+    if !args.steam_args.iter().any(|arg| arg == "-steamdeck") {
+        args.steam_args.push("-steamdeck".to_string());
+    }
+}
+
+/// Whether we were launched by Sunshine for a client stream
+fn sunshine_client_connected() -> bool {
+    ["SUNSHINE_CLIENT_WIDTH", "SUNSHINE_CLIENT_HEIGHT"]
+        .iter()
+        .all(|var| std::env::var_os(var).is_some())
+}
+
 /// Apply Sunshine client environment variables as fallback for CLI args
 /// These are set by Sunshine when launching applications:
 /// - SUNSHINE_CLIENT_WIDTH: Client's horizontal resolution
 /// - SUNSHINE_CLIENT_HEIGHT: Client's vertical resolution
 /// - SUNSHINE_CLIENT_FPS: Client's framerate setting
+/// - SUNSHINE_CLIENT_HDR: Whether the client streams in HDR; an SDR client
+///   turns HDR off, an HDR one gets it wherever the display supports it
 fn apply_sunshine_env_fallbacks(args: &mut Args) {
     // Only apply fallbacks if the corresponding CLI args weren't provided
     if args.resolution.is_none() {
@@ -235,88 +663,64 @@ fn apply_sunshine_env_fallbacks(args: &mut Args) {
 
     if args.refresh_rate.is_none() {
         if let Ok(fps) = std::env::var("SUNSHINE_CLIENT_FPS") {
-            if let Ok(rate) = fps.parse::<u32>() {
+            if let Ok(rate) = parse_refresh_rate(&fps) {
                 eprintln!("Using Sunshine client FPS as refresh rate: {}Hz", rate);
                 args.refresh_rate = Some(rate);
+                // The client's FPS says nothing about what the display can do
+                args.clamp_refresh = true;
             }
         }
     }
-}
 
-fn detect_displays() -> Result<Vec<DisplayInfo>> {
-    let mut displays = Vec::new();
-    let drm_path = Path::new("/sys/class/drm");
-
-    for entry in fs::read_dir(drm_path)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        // Look for card*-* directories (e.g., card1-HDMI-A-1)
-        let dir_name = entry.file_name();
-        let dir_name_str = dir_name.to_string_lossy();
-
-        if !dir_name_str.starts_with("card") || !dir_name_str.contains('-') {
-            continue;
-        }
-
-        let status_file = path.join("status");
-        if !status_file.exists() {
-            continue;
-        }
-
-        let status = fs::read_to_string(&status_file)
-            .context("Failed to read status file")?
-            .trim()
-            .to_string();
-
-        if status == "connected" {
-            let modes_file = path.join("modes");
-            if modes_file.exists() {
-                let modes = fs::read_to_string(&modes_file)?;
-                if let Some(resolution) = modes.lines().next() {
-                    let (width, height) = parse_resolution(resolution)?;
-
-                    displays.push(DisplayInfo {
-                        connector_name: dir_name_str.to_string(),
-                        connector_path: path.clone(),
-                        resolution: resolution.to_string(),
-                        width,
-                        height,
-                    });
-                }
-            }
-        }
+    if !args.force_hdr && !args.no_hdr && sunshine_client_hdr() == Some(false) {
+        eprintln!("Sunshine client streams in SDR, disabling HDR");
+        args.no_hdr = true;
     }
-
-    Ok(displays)
 }
 
-fn parse_resolution(res: &str) -> Result<(u32, u32)> {
-    let parts: Vec<&str> = res.trim().split('x').collect();
-    if parts.len() != 2 {
-        anyhow::bail!("Invalid resolution format: {}", res);
+/// SUNSHINE_CLIENT_HDR as a bool; `None` when not streaming or unrecognized
+fn sunshine_client_hdr() -> Option<bool> {
+    match std::env::var("SUNSHINE_CLIENT_HDR").ok()?.trim() {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
     }
-
-    let width = parts[0].parse::<u32>()
-        .context("Invalid width in resolution")?;
-    let height = parts[1].parse::<u32>()
-        .context("Invalid height in resolution")?;
-
-    Ok((width, height))
 }
 
-fn select_display_interactive(displays: &[DisplayInfo]) -> Result<DisplayInfo> {
+fn select_display_interactive(displays: &[DisplayInfo], args: &Args, config: &Config) -> Result<DisplayInfo> {
+    // With --verbose, detect every display up front (in parallel) so the
+    // menu can show what each one supports
+    let probed: HashMap<String, ProbeResult> = if args.verbose {
+        println!("Detecting display capabilities...");
+        spawn_capability_probes(displays, args, config).iter().collect()
+    } else {
+        HashMap::new()
+    };
+
     println!("\n=== Gaming Display Selection ===\n");
 
     for (i, display) in displays.iter().enumerate() {
-        println!("  [{}] {} - {}", i + 1, display.connector_name, display.resolution);
+        match probed.get(&display.connector_name) {
+            Some(result) => println!("  [{}] {} - {} ({})", i + 1, display.connector_name, display.resolution, capability_summary(result)),
+            None => println!("  [{}] {} - {}", i + 1, display.connector_name, display.resolution),
+        }
     }
 
-    print!("\nSelect display (1-{}): ", displays.len());
+    let timeout = args.select_timeout
+        .filter(|&secs| secs > 0)
+        .map(Duration::from_secs);
+    match timeout {
+        Some(timeout) => print!("\nSelect display (1-{}, [1] in {}s): ", displays.len(), timeout.as_secs()),
+        None => print!("\nSelect display (1-{}): ", displays.len()),
+    }
     io::stdout().flush()?;
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
+    let Some(input) = read_line_timeout(timeout)? else {
+        println!("\nNo answer, using first display: {} at {}",
+                 displays[0].connector_name, displays[0].resolution);
+        pause(Duration::from_secs(1));
+        return Ok(displays[0].clone());
+    };
 
     let choice: usize = input.trim().parse()
         .context("Invalid input")?;
@@ -324,416 +728,641 @@ fn select_display_interactive(displays: &[DisplayInfo]) -> Result<DisplayInfo> {
     if choice < 1 || choice > displays.len() {
         println!("Invalid choice, using first display: {} at {}",
                  displays[0].connector_name, displays[0].resolution);
-        thread::sleep(Duration::from_secs(1));
+        pause(Duration::from_secs(1));
         Ok(displays[0].clone())
     } else {
         let selected = &displays[choice - 1];
-        println!("Using {} at {}", selected.connector_name, selected.resolution);
-        println!();
-        thread::sleep(Duration::from_secs(2));
+        info!("Using {} at {}", selected.connector_name, selected.resolution);
+        info!();
+        pause(Duration::from_secs(2));
         Ok(selected.clone())
     }
 }
 
-fn select_display_launcher(displays: &[DisplayInfo], launcher_cmd: &str) -> Result<DisplayInfo> {
-    // Create list of display options
-    let options: Vec<String> = displays
-        .iter()
-        .map(|d| format!("{} - {}", d.connector_name, d.resolution))
-        .collect();
-    let options_text = options.join("\n");
+/// Read a line from stdin, or `None` if none arrives within `timeout`.
+/// Polls instead of reading on another thread, so nothing is left blocked on
+/// stdin to swallow the answer to a later prompt.
+fn read_line_timeout(timeout: Option<Duration>) -> Result<Option<String>> {
+    if let Some(timeout) = timeout {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let mut fds = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let millis = libc::c_int::try_from(remaining.as_millis()).unwrap_or(libc::c_int::MAX);
+            // SAFETY: `fds` is a live pollfd and the count says there's one
+            let ready = unsafe { libc::poll(&mut fds, 1, millis) };
+            if ready < 0 {
+                // A signal that didn't stop us (e.g. a terminal resize)
+                // interrupts the wait; keep waiting for what's left of it
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(error).context("Failed to wait for input");
+            }
+            if ready == 0 {
+                return Ok(None);
+            }
+            break;
+        }
+    }
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(Some(input))
+}
 
-    // Parse launcher command into program and arguments
-    let parts: Vec<&str> = launcher_cmd.split_whitespace().collect();
-    if parts.is_empty() {
-        anyhow::bail!("Launcher command is empty");
+/// Ask which of the refresh rates the EDID lists for the display's
+/// resolution to use (--pick-refresh); `None` with nothing to choose from.
+/// Like the display prompt, --select-timeout takes the highest rate when
+/// nobody answers.
+fn select_refresh_rate_interactive(display: &DisplayInfo, caps: &DisplayCapabilities, args: &Args) -> Result<Option<u32>> {
+    let rates = caps.refresh_rates_at(display.width, display.height);
+    if rates.len() < 2 {
+        return Ok(None);
     }
 
-    let (program, args) = (parts[0], &parts[1..]);
+    println!("=== Refresh Rate for {} ===\n", display.resolution);
+    for (i, rate) in rates.iter().enumerate() {
+        println!("  [{}] {}Hz", i + 1, rate);
+    }
 
-    // Spawn the launcher process with piped stdin/stdout
-    let mut child = Command::new(program)
-        .args(args)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()
-        .context(format!("Failed to spawn launcher: {}", launcher_cmd))?;
+    let timeout = args.select_timeout
+        .filter(|&secs| secs > 0)
+        .map(Duration::from_secs);
+    match timeout {
+        Some(timeout) => print!("\nSelect refresh rate (1-{}, Enter for {}Hz, [1] in {}s): ", rates.len(), rates[0], timeout.as_secs()),
+        None => print!("\nSelect refresh rate (1-{}, Enter for {}Hz): ", rates.len(), rates[0]),
+    }
+    io::stdout().flush()?;
 
-    // Write options to launcher's stdin
-    if let Some(mut stdin) = child.stdin.take() {
-        stdin.write_all(options_text.as_bytes())
-            .context("Failed to write to launcher stdin")?;
+    let Some(input) = read_line_timeout(timeout)? else {
+        println!("\nNo answer, using {}Hz", rates[0]);
+        return Ok(Some(rates[0]));
+    };
+    if input.trim().is_empty() {
+        return Ok(Some(rates[0]));
     }
 
-    // Read selection from launcher's stdout
-    let output = child.wait_with_output()
-        .context("Failed to wait for launcher")?;
+    let choice: usize = input.trim().parse()
+        .context("Invalid input")?;
+    let rate = match rates.get(choice.wrapping_sub(1)) {
+        Some(&rate) => rate,
+        None => {
+            println!("Invalid choice, using {}Hz", rates[0]);
+            rates[0]
+        }
+    };
+    info!("Using {}Hz\n", rate);
+    Ok(Some(rate))
+}
+
+/// Detected capabilities and their warnings, as the menus and TUI keep them
+type ProbeResult = Result<(DisplayCapabilities, Vec<String>)>;
+
+/// Detected capabilities for the text menu, e.g. "VRR, HDR, 120Hz"
+fn capability_summary(probed: &ProbeResult) -> String {
+    match probed {
+        Ok((caps, warnings)) => {
+            let mut parts = Vec::new();
+            if caps.vrr {
+                parts.push("VRR".to_string());
+            }
+            if caps.hdr {
+                parts.push("HDR".to_string());
+            }
+            parts.push(format!("{}Hz", caps.max_refresh_rate));
+            if !warnings.is_empty() {
+                parts.push("⚠".to_string());
+            }
+            parts.join(", ")
+        }
+        Err(_) => "detection failed".to_string(),
+    }
+}
 
-    if !output.status.success() {
-        anyhow::bail!("Launcher exited with non-zero status (user may have cancelled)");
+fn detect_capabilities(runner: &dyn CommandRunner, display: &DisplayInfo, args: &Args) -> Result<DisplayCapabilities> {
+    let (caps, warnings) = probe_capabilities(runner, display, args)?;
+    for warning in &warnings {
+        eprintln!("⚠ {}", warning);
     }
 
-    let selection = String::from_utf8(output.stdout)
-        .context("Launcher output is not valid UTF-8")?
-        .trim()
-        .to_string();
+    // Print detected capabilities
+    print_capabilities(&caps, display);
 
-    if selection.is_empty() {
-        anyhow::bail!("No display selected");
+    Ok(caps)
+}
+
+fn print_capabilities(caps: &DisplayCapabilities, display: &DisplayInfo) {
+    if let (true, Some(min), Some(max)) = (caps.vrr, caps.vrr_min, caps.vrr_max) {
+        info!("✓ VRR/Adaptive Sync supported ({}-{}Hz)", min, max);
+    } else if caps.vrr {
+        info!("✓ VRR/Adaptive Sync supported");
+    } else {
+        info!("✗ VRR/Adaptive Sync not detected");
     }
 
-    // Find the matching display by parsing the selection
-    // Format is "connector_name - resolution"
-    let connector_name = selection
-        .split(" - ")
-        .next()
-        .context("Invalid selection format")?;
+    if caps.hdr {
+        info!("✓ HDR supported");
+    } else {
+        info!("✗ HDR not detected");
+    }
 
-    displays
-        .iter()
-        .find(|d| d.connector_name == connector_name)
-        .cloned()
-        .context(format!("Selected display '{}' not found", connector_name))
-}
-
-fn detect_capabilities(display: &DisplayInfo, args: &Args) -> Result<DisplayCapabilities> {
-    if args.safe_mode {
-        println!("⚠ Safe mode enabled - using conservative defaults");
-        return Ok(DisplayCapabilities {
-            vrr: false,
-            hdr: false,
-            max_refresh_rate: 60,
-            max_bpc: 8,
-        });
+    match caps.max_bpc {
+        12 => info!("✓ 12-bit color depth supported"),
+        10 => info!("✓ 10-bit color depth supported"),
+        _ => info!("✓ 8-bit color depth (standard)"),
     }
 
-    let edid_file = display.connector_path.join("edid");
+    info!("✓ Maximum refresh rate: {}Hz", caps.max_refresh_rate);
 
-    if !edid_file.exists() || !edid_file.is_file() {
-        println!("⚠ EDID file not accessible, using defaults");
-        return Ok(default_capabilities(display));
+    if let (Some((width_mm, height_mm)), Some(dpi)) = (caps.physical_size_mm, caps.dpi(display.width)) {
+        info!("✓ Physical size: {}x{} mm ({:.0} DPI at {})", width_mm, height_mm, dpi, display.resolution);
     }
+}
+
+/// Fallback steps tried in order after a failed launch, each one degrading
+/// the settings a little further than the previous
+#[derive(Debug, Clone, Copy)]
+enum FallbackStep {
+    DisableHdr,
+    DisableVrr,
+    Refresh60,
+    Resolution1080p,
+}
 
-    // Read EDID binary data
-    let edid_data = fs::read(&edid_file)
-        .context("Failed to read EDID file")?;
+impl FallbackStep {
+    const LADDER: [FallbackStep; 4] = [
+        FallbackStep::DisableHdr,
+        FallbackStep::DisableVrr,
+        FallbackStep::Refresh60,
+        FallbackStep::Resolution1080p,
+    ];
 
-    if edid_data.is_empty() {
-        println!("⚠ EDID file is empty, using defaults");
-        return Ok(default_capabilities(display));
+    fn description(self) -> &'static str {
+        match self {
+            FallbackStep::DisableHdr => "with HDR disabled",
+            FallbackStep::DisableVrr => "with VRR disabled",
+            FallbackStep::Refresh60 => "at 60Hz",
+            FallbackStep::Resolution1080p => "at 1920x1080",
+        }
     }
 
-    // Use edid-decode to parse EDID
-    let edid_decode_output = Command::new("edid-decode")
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::null())
-        .spawn()
-        .and_then(|mut child| {
-            if let Some(mut stdin) = child.stdin.take() {
-                stdin.write_all(&edid_data)?;
+    /// Apply this step on top of the previous ones.
+    /// Returns false if it wouldn't change anything, so the retry can be skipped.
+    fn apply(self, display: &mut DisplayInfo, caps: &mut DisplayCapabilities) -> bool {
+        match self {
+            FallbackStep::DisableHdr => std::mem::replace(&mut caps.hdr, false),
+            FallbackStep::DisableVrr => std::mem::replace(&mut caps.vrr, false),
+            FallbackStep::Refresh60 => std::mem::replace(&mut caps.max_refresh_rate, 60) != 60,
+            FallbackStep::Resolution1080p => {
+                if display.width == 1920 && display.height == 1080 {
+                    return false;
+                }
+                display.width = 1920;
+                display.height = 1080;
+                display.resolution = "1920x1080".to_string();
+                true
             }
-            child.wait_with_output()
-        });
+        }
+    }
+}
 
-    let capabilities = if let Ok(output) = edid_decode_output {
-        let edid_text = String::from_utf8_lossy(&output.stdout);
-        parse_edid_capabilities(&edid_text, display)
-    } else {
-        println!("⚠ Could not run edid-decode, using defaults");
-        default_capabilities(display)
-    };
+fn launch_gamescope(runner: &dyn CommandRunner, display: &DisplayInfo, caps: &DisplayCapabilities, args: &Args, config: &Config) -> Result<()> {
+    // Without DRM master gamescope fails in confusing ways, over and over
+    if !args.dry_run && drm_master_held(display) == Some(true) {
+        anyhow::bail!(
+            "The GPU driving {} is in use by another graphics session (it holds DRM master), so gamescope can't take over the display.\n\
+             Switch to a free VT (e.g. Ctrl+Alt+F3) and run console-mode there, or stop the other session (desktop or display manager).\n\
+             To run in a window inside the current desktop instead, use --force-nested.",
+            display.connector_name
+        );
+    }
+
+    if let Some(bpc) = output_bpc(caps, args) {
+        if args.dry_run {
+            println!("Would set max bpc to {} on {}", bpc, display.connector_name);
+        } else if let Err(e) = set_max_bpc(display, bpc) {
+            // The driver's own default still works, just maybe at 8-bit
+            eprintln!("⚠ {:#}", e);
+        } else {
+            info!("Set max bpc to {} on {}", bpc, display.connector_name);
+        }
+    }
+
+    if let Some(ycbcr420) = hdmi_ycbcr420(display, caps, args) {
+        let format = if ycbcr420 { "YCbCr 4:2:0" } else { "RGB" };
+        if args.dry_run {
+            println!("Would switch {} to {}", display.connector_name, format);
+        } else if let Err(e) = set_ycbcr420(display, ycbcr420) {
+            eprintln!("⚠ {:#}", e);
+        } else {
+            info!("Switched {} to {}", display.connector_name, format);
+        }
+    }
 
-    // Apply user overrides
-    let mut caps = capabilities;
+    if args.dry_run {
+        println!("{}", format_command(&gamescope_command(display, caps, args)));
+        return Ok(());
+    }
 
-    if args.force_vrr {
-        caps.vrr = true;
-    } else if args.no_vrr {
-        caps.vrr = false;
+    let mut stderr_tail = VecDeque::new();
+    if run_gamescope(runner, display, caps, args, &mut stderr_tail)? {
+        return Ok(());
     }
 
-    if args.force_hdr {
-        caps.hdr = true;
-    } else if args.no_hdr {
-        caps.hdr = false;
+    // A test launch reports the settings as failing rather than trying safer ones
+    if args.test_launch {
+        print_stderr_tail(&stderr_tail);
+        return Err(Error::GamescopeStartFailed { fallbacks_tried: false }.into());
     }
 
-    if let Some(rate) = args.refresh_rate {
-        caps.max_refresh_rate = rate;
+    eprintln!("\n======================================");
+    eprintln!("Gamescope failed to start!");
+    eprintln!("======================================\n");
+    print_stderr_tail(&stderr_tail);
+
+    // Offer to retry with progressively safer options. With a startup
+    // timeout there may be no one to answer, so just go ahead.
+    if args.startup_timeout.is_some() {
+        info!("Retrying with safe options...");
+    } else if !prompt_retry(args, config, &stderr_tail)? {
+        return Err(Error::GamescopeStartFailed { fallbacks_tried: false }.into());
     }
 
-    // Print detected capabilities
-    print_capabilities(&caps);
+    let mut display = display.clone();
+    let mut caps = caps.clone();
+
+    for step in FallbackStep::LADDER {
+        if !step.apply(&mut display, &mut caps) {
+            continue;
+        }
+
+        info!("\nRetrying {}...", step.description());
+        pause(Duration::from_secs(2));
+
+        if run_gamescope(runner, &display, &caps, args, &mut stderr_tail)? {
+            return Ok(());
+        }
+
+        eprintln!("\nGamescope failed to start {}", step.description());
+        print_stderr_tail(&stderr_tail);
+    }
+
+    Err(Error::GamescopeStartFailed { fallbacks_tried: true }.into())
+}
+
+/// Whether to force YCbCr 4:2:0 (`Some(true)`) or RGB (`Some(false)`) on an
+/// HDMI output, or leave the format to the driver (`None`)
+fn hdmi_ycbcr420(display: &DisplayInfo, caps: &DisplayCapabilities, args: &Args) -> Option<bool> {
+    if !display.output_name().starts_with("HDMI") {
+        return None;
+    }
 
-    Ok(caps)
+    match args.color_format {
+        ColorFormat::Rgb => Some(false),
+        ColorFormat::Ycbcr420 => Some(true),
+        ColorFormat::Auto => {
+            let bpc = output_bpc(caps, args).unwrap_or(8);
+            match hdmi_pixel_format(display, caps, bpc, ColorFormat::Auto) {
+                Some(true) => {
+                    info!("{} at {}Hz and {}-bit needs more bandwidth than the HDMI link has in RGB",
+                        display.resolution, caps.max_refresh_rate, bpc);
+                    Some(true)
+                }
+                format => {
+                    if format.is_none() {
+                        info!("{} at {}Hz and {}-bit doesn't fit the HDMI link in RGB, and the display doesn't list it for 4:2:0",
+                            display.resolution, caps.max_refresh_rate, bpc);
+                    }
+                    // The switch outlives the session, so undo one an earlier launch left on
+                    ycbcr420_forced(display).unwrap_or(false).then_some(false)
+                }
+            }
+        }
+    }
 }
 
-fn parse_edid_capabilities(edid_text: &str, display: &DisplayInfo) -> DisplayCapabilities {
-    let mut caps = DisplayCapabilities {
-        vrr: false,
-        hdr: false,
-        max_refresh_rate: 60,
-        max_bpc: 8,
+/// Ask whether to retry after gamescope failed to start, with A/B on a
+/// controller as well as Enter/Esc, since a couch setup may have no keyboard.
+/// Without a terminal to ask on, retries.
+fn prompt_retry(args: &Args, config: &Config, stderr_tail: &VecDeque<String>) -> Result<bool> {
+    let buttons = controller_buttons(args, config)?;
+    let gamepads = open_gamepads(true);
+
+    let terminal_guard = match TerminalGuard::enter() {
+        Ok(guard) => guard,
+        Err(e) => {
+            debug_log(&format!("No terminal for the retry prompt ({}), retrying", e));
+            return Ok(true);
+        }
     };
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
 
-    // Check for VRR/FreeSync/G-SYNC
-    let vrr_patterns = [
-        "Variable Refresh Rate",
-        "FreeSync",
-        "G-SYNC Compatible",
-        "VESA VRR",
-        "Vendor-Specific Data Block (AMD)",
-    ];
+    let (tx, rx) = mpsc::channel::<InputEvent>();
+    spawn_controller_reader(tx, buttons, args.tui_rumble, gamepads);
 
-    for pattern in &vrr_patterns {
-        if edid_text.contains(pattern) {
-            caps.vrr = true;
-            break;
+    let retry = loop {
+        if signals::terminated() {
+            break false;
         }
-    }
+        terminal.draw(|f| render_retry_prompt(f, stderr_tail))?;
 
-    // Check for HDR
-    let hdr_patterns = [
-        "HDR Static Metadata",
-        "HDR10",
-        "SMPTE ST 2084",
-    ];
-
-    for pattern in &hdr_patterns {
-        if edid_text.contains(pattern) {
-            caps.hdr = true;
-            break;
+        if let Ok(input) = rx.try_recv() {
+            match input {
+                InputEvent::Select => break true,
+                InputEvent::Quit => break false,
+                _ => {}
+            }
         }
-    }
-
-    // Check for color depth
-    if edid_text.contains("12 bits per") || edid_text.contains("Bits per primary color channel: 12") {
-        caps.max_bpc = 12;
-    } else if edid_text.contains("10 bits per") || edid_text.contains("Bits per primary color channel: 10") {
-        caps.max_bpc = 10;
-    }
 
-    // Extract maximum refresh rate
-    let refresh_regex = Regex::new(r"(\d+)\.?\d*\s*Hz").ok();
-    if let Some(re) = refresh_regex {
-        let mut max_rate = 60;
-        for cap in re.captures_iter(edid_text) {
-            if let Ok(rate) = cap[1].parse::<u32>() {
-                if rate > max_rate && rate <= 500 {  // Sanity check
-                    max_rate = rate;
+        if event::poll(Duration::from_millis(50))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    match key.code {
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break false,
+                        KeyCode::Enter | KeyCode::Char(' ') => break true,
+                        KeyCode::Esc | KeyCode::Char('q') => break false,
+                        _ => {}
+                    }
                 }
             }
         }
-        caps.max_refresh_rate = max_rate;
-    }
-
-    // Fallback: assume based on resolution if we didn't get a good refresh rate
-    if caps.max_refresh_rate < 60 {
-        caps.max_refresh_rate = if display.width >= 2560 { 144 } else { 60 };
-    }
+    };
 
-    caps
+    drop(terminal_guard);
+    Ok(retry)
 }
 
-fn default_capabilities(display: &DisplayInfo) -> DisplayCapabilities {
-    DisplayCapabilities {
-        vrr: false,
-        hdr: false,
-        max_refresh_rate: if display.width >= 2560 { 144 } else { 60 },
-        max_bpc: 8,
-    }
-}
+fn render_retry_prompt(frame: &mut Frame, stderr_tail: &VecDeque<String>) {
+    let area = frame.area();
+    let popup_area = centered_rect(60, if stderr_tail.is_empty() { 30 } else { 50 }, area);
 
-fn print_capabilities(caps: &DisplayCapabilities) {
-    if caps.vrr {
-        println!("✓ VRR/Adaptive Sync supported");
-    } else {
-        println!("✗ VRR/Adaptive Sync not detected");
+    let mut content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Gamescope failed to start!",
+            Style::default().fg(theme().bad).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from("Retry with safer settings, one step at a time:"),
+        Line::from(Span::styled(
+            "HDR off, VRR off, 60Hz, then 1920x1080",
+            Style::default().fg(theme().hint),
+        )),
+    ];
+    if !stderr_tail.is_empty() {
+        content.push(Line::from(""));
+        content.push(Line::from("Last gamescope output:"));
+        content.extend(stderr_tail.iter().map(|line| {
+            Line::from(Span::styled(line.as_str(), Style::default().fg(theme().hint)))
+        }));
     }
 
-    if caps.hdr {
-        println!("✓ HDR supported");
-    } else {
-        println!("✗ HDR not detected");
-    }
+    let paragraph = Paragraph::new(content)
+        .block(
+            Block::default()
+                .title(" Console Mode ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme().bad)),
+        )
+        .alignment(Alignment::Center);
+    frame.render_widget(paragraph, popup_area);
 
-    match caps.max_bpc {
-        12 => println!("✓ 12-bit color depth supported"),
-        10 => println!("✓ 10-bit color depth supported"),
-        _ => println!("✓ 8-bit color depth (standard)"),
-    }
+    let help_area = Rect {
+        x: popup_area.x,
+        y: popup_area.y + popup_area.height,
+        width: popup_area.width,
+        height: 1,
+    };
 
-    println!("✓ Maximum refresh rate: {}Hz", caps.max_refresh_rate);
+    if help_area.y + help_area.height <= area.height {
+        let help_text = Paragraph::new(Line::from(vec![
+            Span::styled("[Enter/A] ", Style::default().fg(theme().select_key)),
+            Span::raw("Retry  "),
+            Span::styled("[Esc/B] ", Style::default().fg(theme().back_key)),
+            Span::raw("Quit"),
+        ]));
+        frame.render_widget(help_text, help_area);
+    }
 }
 
-fn build_gamescope_args(display: &DisplayInfo, caps: &DisplayCapabilities, args: &Args) -> Vec<String> {
-    let mut gs_args = vec![
-        "-W".to_string(), display.width.to_string(),
-        "-H".to_string(), display.height.to_string(),
-        "-r".to_string(), caps.max_refresh_rate.to_string(),
-    ];
+/// Run gamescope once with the given settings and return whether it worked:
+/// a clean exit, and with --startup-timeout not one within that many seconds,
+/// since that's a crash rather than the user quitting
+fn run_gamescope(runner: &dyn CommandRunner, display: &DisplayInfo, caps: &DisplayCapabilities, args: &Args, stderr_tail: &mut VecDeque<String>) -> Result<bool> {
+    let gs_args = build_gamescope_args(display, caps, args);
 
-    // Specify which output to use (strip "cardX-" prefix if present)
-    let output_name = if let Some(stripped) = display.connector_name.split_once('-') {
-        stripped.1.to_string()
+    info!("Launching gamescope with: {}", gs_args.join(" "));
+    info!();
+    pause(Duration::from_secs(1));
+
+    let started = Instant::now();
+    let mut cmd = gamescope_command(display, caps, args);
+    let status = if args.capture_gamescope {
+        stderr_tail.clear();
+        // With the debug log on stderr, logging the line already shows it
+        let echo = !QUIET.get().copied().unwrap_or(false) && !logging::logs_to_stderr();
+        runner.status_with_stderr(&mut cmd, &mut |line| {
+            if echo {
+                eprintln!("{}", line);
+            }
+            debug_log(&format!("gamescope: {}", line));
+            if stderr_tail.len() == GAMESCOPE_TAIL_LINES {
+                stderr_tail.pop_front();
+            }
+            stderr_tail.push_back(line.to_string());
+        })
     } else {
-        display.connector_name.clone()
-    };
-    gs_args.extend(["--prefer-output".to_string(), output_name]);
-
-    if caps.vrr {
-        gs_args.push("--adaptive-sync".to_string());
+        runner.status(&mut cmd)
     }
+    .map_err(|source| Error::GamescopeSpawn { mode: LaunchMode::Native, source })?;
 
-    if caps.hdr {
-        gs_args.extend(["--hdr-enabled".to_string(), "--hdr-itm-enable".to_string()]);
+    if let Some(secs) = args.startup_timeout {
+        let ran_for = started.elapsed();
+        if ran_for < Duration::from_secs(secs) {
+            debug_log(&format!("gamescope exited ({}) after {:.1}s, within --startup-timeout", status, ran_for.as_secs_f32()));
+            return Ok(false);
+        }
     }
 
-    // Add MangoHud
-    gs_args.push("--mangoapp".to_string());
-
-    // Fullscreen and expose Wayland
-    gs_args.extend(["-f".to_string(), "-e".to_string()]);
+    Ok(status.success())
+}
 
-    // Add any extra user-provided args
-    gs_args.extend(args.extra_args.clone());
+/// Lines of gamescope's stderr kept to show when it fails (--capture-gamescope)
+const GAMESCOPE_TAIL_LINES: usize = 8;
 
-    gs_args
+/// Show the end of gamescope's stderr after a failed start, since it
+/// usually says why
+fn print_stderr_tail(stderr_tail: &VecDeque<String>) {
+    if stderr_tail.is_empty() {
+        return;
+    }
+    eprintln!("Last gamescope output:");
+    for line in stderr_tail {
+        eprintln!("  {}", line);
+    }
+    eprintln!();
 }
 
-fn launch_gamescope(display: &DisplayInfo, caps: &DisplayCapabilities, args: &Args) -> Result<()> {
-    let gs_args = build_gamescope_args(display, caps, args);
+/// Format a command as a shell-quoted string, as printed by --dry-run,
+/// with any variables it sets as `KEY=VALUE` in front
+fn format_command(cmd: &Command) -> String {
+    let env = cmd.get_envs().filter_map(|(key, value)| {
+        Some(format!("{}={}", key.to_string_lossy(), shell_quote(&value?.to_string_lossy())))
+    });
+    let command = std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| shell_quote(&arg.to_string_lossy()));
 
-    println!("Launching gamescope with: {}", gs_args.join(" "));
-    println!();
-    thread::sleep(Duration::from_secs(1));
+    env.chain(command).collect::<Vec<_>>().join(" ")
+}
 
+fn launch_gamescope_fallback(args: &Args) -> Result<()> {
     let gamescope_bin = args.gamescope_bin.as_deref()
         .unwrap_or(Path::new("gamescope"));
-    let steam_bin = args.steam_bin.as_deref()
-        .unwrap_or(Path::new("steam"));
 
     let mut cmd = Command::new(gamescope_bin);
-    cmd.args(&gs_args)
-        .arg("--")
-        .arg(steam_bin)
-        .arg("-bigpicture")
-        .args(&args.steam_args);
-
-    let status = cmd.status()
-        .context("Failed to launch gamescope")?;
+    cmd.args(["-W", "1920", "-H", "1080", "-r", "60"]);
+    cmd.args(window_mode_args(args));
+    cmd.args(steam_integration_args(args));
+    cmd.args(toggle_args(args));
+    append_session_command(&mut cmd, args);
+
+    let context = HookContext { connector: None, width: 1920, height: 1080, refresh_rate: 60 };
+    with_launch_hooks(args, &context, || run_session_command(&mut cmd, args, LaunchMode::Fallback))
+}
 
-    if !status.success() {
-        eprintln!("\n======================================");
-        eprintln!("Gamescope failed to start!");
-        eprintln!("======================================\n");
-
-        // Offer to retry with safe options
-        print!("Press Enter to retry with safe options, or Ctrl+C to exit: ");
-        io::stdout().flush()?;
-
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-
-        println!("\nRetrying with safe options...");
-        thread::sleep(Duration::from_secs(2));
-
-        let width_str = display.width.to_string();
-        let height_str = display.height.to_string();
-        let safe_args = vec![
-            "-W", width_str.as_str(),
-            "-H", height_str.as_str(),
-            "-r", "120",
-            "-f", "-e",
-        ];
+/// Check if we're running inside another compositor
+///
+/// WAYLAND_DISPLAY and DISPLAY alone aren't trusted, since they can leak
+/// into a TTY login or come from SSH X forwarding; the socket they name has
+/// to exist locally as well.
+fn is_running_nested(args: &Args) -> bool {
+    if args.force_native {
+        return false;
+    }
+    if args.force_nested {
+        return true;
+    }
 
-        let mut safe_cmd = Command::new(gamescope_bin);
-        safe_cmd.args(safe_args)
-            .arg("--")
-            .arg(steam_bin)
-            .arg("-bigpicture")
-            .args(&args.steam_args);
+    let wayland = std::env::var_os("WAYLAND_DISPLAY")
+        .and_then(|name| wayland_socket_path(Path::new(&name)));
+    let x11 = std::env::var("DISPLAY").ok()
+        .and_then(|display| x11_socket_path(&display));
+
+    [wayland, x11].into_iter().flatten().any(|socket| {
+        let exists = socket.exists();
+        debug_log(&format!("Nested check: {} {}", socket.display(),
+            if exists { "exists" } else { "missing" }));
+        exists
+    })
+}
 
-        safe_cmd.status()
-            .context("Failed to launch gamescope in safe mode")?;
+/// WAYLAND_DISPLAY is either an absolute socket path or a name under XDG_RUNTIME_DIR
+fn wayland_socket_path(name: &Path) -> Option<PathBuf> {
+    if name.as_os_str().is_empty() {
+        return None;
     }
+    if name.is_absolute() {
+        return Some(name.to_path_buf());
+    }
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")?;
+    Some(Path::new(&runtime_dir).join(name))
+}
 
-    Ok(())
+/// Socket of a local X display (":0" or ":0.0"); displays on another host,
+/// like SSH's "localhost:10.0", can't be a compositor we're nested in
+fn x11_socket_path(display: &str) -> Option<PathBuf> {
+    let number = display.strip_prefix(':')?
+        .split('.')
+        .next()?;
+    number.parse::<u32>().ok()?;
+    Some(PathBuf::from(format!("/tmp/.X11-unix/X{}", number)))
 }
 
-fn launch_gamescope_fallback(args: &Args) -> Result<()> {
+/// Explain the nested mode and its harmless warnings, then launch nested
+fn launch_nested_session(args: &Args) -> Result<()> {
+    info!("Detected nested environment (running inside another compositor)");
+    info!("Launching in nested Wayland mode...");
+    info!("\nNote: You may see some warnings from gamescope/Mesa:");
+    info!("  - 'No CAP_SYS_NICE' - normal, doesn't affect gaming performance");
+    info!("  - 'libdecor warnings' - expected in nested mode");
+    info!("  - 'RADV not conformant' - safe to ignore, RADV works great for gaming");
+    info!("  - 'vk_khr_present_wait overridden' - informational only\n");
+    pause(Duration::from_secs(2));
+    launch_gamescope_nested(args)
+}
+
+fn launch_gamescope_nested(args: &Args) -> Result<()> {
     let gamescope_bin = args.gamescope_bin.as_deref()
         .unwrap_or(Path::new("gamescope"));
-    let steam_bin = args.steam_bin.as_deref()
-        .unwrap_or(Path::new("steam"));
 
-    let mut cmd = Command::new(gamescope_bin);
-    cmd.args(["-W", "1920", "-H", "1080", "-r", "60", "-f", "-e", "--"])
-        .arg(steam_bin)
-        .arg("-bigpicture")
-        .args(&args.steam_args);
+    // Determine resolution from args or use defaults
+    let (width, height) = if let Some(ref res) = args.resolution {
+        parse_resolution(res)?
+    } else {
+        (1920, 1080)
+    };
 
-    cmd.status()
-        .context("Failed to launch gamescope in fallback mode")?;
+    let refresh_rate = whole_hz(args.refresh_rate.unwrap_or(60.0));
 
-    Ok(())
-}
+    let gs_args = build_nested_args(width, height, refresh_rate, args);
+
+    info!("Launching gamescope in nested mode with: {}", gs_args.join(" "));
+    info!();
+    pause(Duration::from_secs(1));
+
+    let mut cmd = Command::new(gamescope_bin);
+    cmd.args(&gs_args);
+    append_session_command(&mut cmd, args);
 
-fn is_running_nested() -> bool {
-    // Check if we're running inside another compositor
-    // WAYLAND_DISPLAY indicates we're in a Wayland session
-    // DISPLAY indicates we're in an X11 session
-    std::env::var("WAYLAND_DISPLAY").is_ok() || std::env::var("DISPLAY").is_ok()
+    let context = HookContext { connector: None, width, height, refresh_rate };
+    with_launch_hooks(args, &context, || run_session_command(&mut cmd, args, LaunchMode::Nested))
 }
 
-fn launch_gamescope_nested(args: &Args) -> Result<()> {
+/// Launch gamescope on a virtual output for streaming hosts without a monitor.
+/// The size comes from --resolution/--refresh-rate, which Sunshine's client
+/// settings fill in when not given.
+fn launch_gamescope_headless(args: &Args) -> Result<()> {
     let gamescope_bin = args.gamescope_bin.as_deref()
         .unwrap_or(Path::new("gamescope"));
-    let steam_bin = args.steam_bin.as_deref()
-        .unwrap_or(Path::new("steam"));
 
-    // Determine resolution from args or use defaults
     let (width, height) = if let Some(ref res) = args.resolution {
         parse_resolution(res)?
     } else {
         (1920, 1080)
     };
 
-    let refresh_rate = args.refresh_rate.unwrap_or(60);
-
-    let mut gs_args = vec![
-        "-W".to_string(), width.to_string(),
-        "-H".to_string(), height.to_string(),
-        "-r".to_string(), refresh_rate.to_string(),
-        "--nested-width".to_string(), width.to_string(),
-        "--nested-height".to_string(), height.to_string(),
-        "--nested-refresh".to_string(), refresh_rate.to_string(),
-        "-e".to_string(),  // Expose Wayland socket
-    ];
+    let refresh_rate = args.refresh_rate.unwrap_or(60.0);
+    // A virtual output has no EDID to say whether it can do HDR, so it's on
+    // only when asked for
+    let hdr = args.force_hdr || (!args.no_hdr && sunshine_client_hdr() == Some(true));
+    let gs_args = build_virtual_output_args(width, height, refresh_rate, hdr, args);
 
-    // Add MangoHud if desired
-    gs_args.push("--mangoapp".to_string());
+    info!("Launching gamescope headless at {}x{}@{}Hz", width, height, refresh_rate);
 
-    // Add any extra user-provided args
-    gs_args.extend(args.extra_args.clone());
+    let mut cmd = Command::new(gamescope_bin);
+    cmd.args(&gs_args);
+    append_session_command(&mut cmd, args);
 
-    println!("Launching gamescope in nested mode with: {}", gs_args.join(" "));
-    println!();
-    thread::sleep(Duration::from_secs(1));
+    let context = HookContext { connector: None, width, height, refresh_rate: whole_hz(refresh_rate) };
+    with_launch_hooks(args, &context, || run_session_command(&mut cmd, args, LaunchMode::Headless))
+}
 
-    let mut cmd = Command::new(gamescope_bin);
-    cmd.args(&gs_args)
-        .arg("--")
-        .arg(steam_bin)
-        .arg("-bigpicture")
-        .args(&args.steam_args);
+/// Run (or with --dry-run, print) a nested, headless or fallback gamescope;
+/// `mode` names which in errors
+fn run_session_command(cmd: &mut Command, args: &Args, mode: LaunchMode) -> Result<()> {
+    if args.dry_run {
+        println!("{}", format_command(cmd));
+        return Ok(());
+    }
 
-    let status = cmd.status()
-        .context("Failed to launch gamescope in nested mode")?;
+    let status = SystemRunner.status(cmd)
+        .map_err(|source| Error::GamescopeSpawn { mode, source })?;
 
     if !status.success() {
-        anyhow::bail!("Gamescope exited with non-zero status");
+        return Err(Error::GamescopeExited { mode, status }.into());
     }
 
     Ok(())
@@ -749,38 +1378,215 @@ enum InputEvent {
     Down,
     Select,
     Quit,
+    ToggleVrr,
+    ToggleHdr,
     AnyButton, // Used in idle watcher mode to detect any controller input
 }
 
+/// Which list the TUI is showing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TuiView {
+    DisplayList,
+    /// Modes of the display highlighted in the display list
+    ModeList,
+    /// Refresh rates of the picked display at the picked mode (--pick-refresh)
+    RefreshList,
+    /// Detected capabilities of the picked display, before launching
+    Confirm,
+}
+
 /// TUI application state
 struct TuiApp {
     displays: Vec<DisplayInfo>,
+    /// Indices into `displays` matching the filter, as listed
+    filtered: Vec<usize>,
+    /// Keyboard filter on connector name and resolution
+    filter: String,
+    /// Typing goes into the filter (after '/') rather than navigating
+    filtering: bool,
     list_state: ListState,
+    view: TuiView,
+    mode_state: ListState,
+    refresh_state: ListState,
     should_quit: bool,
+    /// The picked display, with its resolution set to the picked mode
     selected_display: Option<DisplayInfo>,
+    /// Rows the list popup can show, as of the last render
+    visible_rows: usize,
+    /// Whether the resolution was picked from the mode list rather than defaulted
+    mode_picked: bool,
+    /// View the display (and mode) was picked in, to return to from the
+    /// refresh rate list
+    picked_from: TuiView,
+    /// Show the refresh rate list after picking (--pick-refresh)
+    pick_refresh: bool,
+    /// Refresh rate picked from the refresh rate list
+    refresh_rate: Option<u32>,
+    /// Auto-select the highlighted display after this long without input
+    timeout: Option<Duration>,
+    last_input: Instant,
+    /// Show the confirmation view after picking (off with --tui-no-confirm)
+    confirm: bool,
+    /// View to return to when backing out of the confirmation view
+    confirm_from: TuiView,
+    /// Capabilities and warnings shown in the confirmation view, probed
+    /// by the main loop once a display is picked
+    capabilities: Option<ProbeResult>,
+    /// Capabilities of every display by connector name, filled in as the
+    /// background probes finish (--tui-detect-all); `None` when off
+    probed: Option<HashMap<String, ProbeResult>>,
+    /// VRR and HDR overrides toggled in the TUI, starting from the CLI
+    /// flags; `None` leaves it to detection
+    vrr: Option<bool>,
+    hdr: Option<bool>,
 }
 
 impl TuiApp {
-    fn new(displays: Vec<DisplayInfo>) -> Self {
+    fn new(displays: Vec<DisplayInfo>, preselect: Option<&str>, timeout: Option<Duration>, confirm: bool) -> Self {
         let mut list_state = ListState::default();
         if !displays.is_empty() {
-            list_state.select(Some(0));
+            // Start on the preselected display if it's still connected
+            let index = preselect
+                .and_then(|name| displays.iter().position(|d| d.connector_name == name))
+                .unwrap_or(0);
+            list_state.select(Some(index));
         }
+        // Nothing to auto-select without displays
+        let timeout = timeout.filter(|_| !displays.is_empty());
         Self {
+            filtered: (0..displays.len()).collect(),
             displays,
+            filter: String::new(),
+            filtering: false,
             list_state,
+            view: TuiView::DisplayList,
+            mode_state: ListState::default(),
+            refresh_state: ListState::default(),
             should_quit: false,
             selected_display: None,
+            visible_rows: 0,
+            mode_picked: false,
+            picked_from: TuiView::DisplayList,
+            pick_refresh: false,
+            refresh_rate: None,
+            timeout,
+            last_input: Instant::now(),
+            confirm,
+            confirm_from: TuiView::DisplayList,
+            capabilities: None,
+            probed: None,
+            vrr: None,
+            hdr: None,
+        }
+    }
+
+    fn toggle_vrr(&mut self) {
+        self.vrr = next_override(self.vrr);
+        // The confirmation view shows the result of the overrides
+        self.capabilities = None;
+    }
+
+    fn toggle_hdr(&mut self) {
+        self.hdr = next_override(self.hdr);
+        self.capabilities = None;
+    }
+
+    /// Put the toggled overrides into the args, as if given as flags
+    fn apply_overrides(&self, args: &mut Args) {
+        args.force_vrr = self.vrr == Some(true);
+        args.no_vrr = self.vrr == Some(false);
+        args.force_hdr = self.hdr == Some(true);
+        args.no_hdr = self.hdr == Some(false);
+        if let Some(rate) = self.refresh_rate {
+            args.refresh_rate = Some(rate as f32);
+        }
+    }
+
+    /// Restart the auto-select countdown
+    fn reset_timeout(&mut self) {
+        self.last_input = Instant::now();
+    }
+
+    /// Time left before the highlighted display is auto-selected
+    fn auto_select_remaining(&self) -> Option<Duration> {
+        self.timeout
+            .map(|timeout| timeout.saturating_sub(self.last_input.elapsed()))
+    }
+
+    /// The display highlighted in the display list
+    fn highlighted_display(&self) -> Option<&DisplayInfo> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.filtered.get(i))
+            .map(|&i| &self.displays[i])
+    }
+
+    /// Displays shown in the display list
+    fn filtered_displays(&self) -> impl Iterator<Item = &DisplayInfo> {
+        self.filtered.iter().map(|&i| &self.displays[i])
+    }
+
+    /// Re-run the filter, keeping the highlighted display if it still matches
+    fn apply_filter(&mut self) {
+        let highlighted = self.list_state.selected().and_then(|i| self.filtered.get(i).copied());
+        let filter = self.filter.to_lowercase();
+        self.filtered = self.displays
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| {
+                d.connector_name.to_lowercase().contains(&filter) || d.resolution.contains(&filter)
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        let selected = highlighted
+            .and_then(|h| self.filtered.iter().position(|&i| i == h))
+            .or(if self.filtered.is_empty() { None } else { Some(0) });
+        self.list_state.select(selected);
+        self.scroll_to_selection();
+    }
+
+    /// Leave filter input and show every display again
+    fn clear_filter(&mut self) {
+        self.filter.clear();
+        self.filtering = false;
+        self.apply_filter();
+    }
+
+    /// Modes listed in the mode view
+    fn modes(&self) -> &[(u32, u32)] {
+        self.highlighted_display()
+            .map_or(&[], |display| display.available_modes.as_slice())
+    }
+
+    /// Refresh rates listed in the refresh rate view, once the picked
+    /// display has been probed
+    fn refresh_rates(&self) -> Vec<u32> {
+        match (&self.selected_display, &self.capabilities) {
+            (Some(display), Some(Ok((caps, _)))) => caps.refresh_rates_at(display.width, display.height),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Length and state of the list currently shown
+    fn active_list(&mut self) -> (usize, &mut ListState) {
+        match self.view {
+            TuiView::DisplayList => (self.filtered.len(), &mut self.list_state),
+            TuiView::ModeList => (self.modes().len(), &mut self.mode_state),
+            TuiView::RefreshList => (self.refresh_rates().len(), &mut self.refresh_state),
+            // Nothing to navigate
+            TuiView::Confirm => (0, &mut self.list_state),
         }
     }
 
     fn next(&mut self) {
-        if self.displays.is_empty() {
+        let (len, state) = self.active_list();
+        if len == 0 {
             return;
         }
-        let i = match self.list_state.selected() {
+        let i = match state.selected() {
             Some(i) => {
-                if i >= self.displays.len() - 1 {
+                if i >= len - 1 {
                     0
                 } else {
                     i + 1
@@ -788,39 +1594,161 @@ impl TuiApp {
             }
             None => 0,
         };
-        self.list_state.select(Some(i));
+        state.select(Some(i));
+        self.scroll_to_selection();
     }
 
     fn previous(&mut self) {
-        if self.displays.is_empty() {
+        let (len, state) = self.active_list();
+        if len == 0 {
             return;
         }
-        let i = match self.list_state.selected() {
+        let i = match state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.displays.len() - 1
+                    len - 1
                 } else {
                     i - 1
                 }
             }
             None => 0,
         };
-        self.list_state.select(Some(i));
+        state.select(Some(i));
+        self.scroll_to_selection();
+    }
+
+    /// Scroll the shown list just far enough that the highlighted row is visible
+    fn scroll_to_selection(&mut self) {
+        let rows = self.visible_rows.max(1);
+        let (_, state) = self.active_list();
+        let Some(selected) = state.selected() else {
+            return;
+        };
+        if selected < state.offset() {
+            *state.offset_mut() = selected;
+        } else if selected >= state.offset() + rows {
+            *state.offset_mut() = selected + 1 - rows;
+        }
     }
 
+    /// Drill into the highlighted display's modes, or pick the highlighted mode
     fn select(&mut self) {
-        if let Some(i) = self.list_state.selected() {
-            if i < self.displays.len() {
-                self.selected_display = Some(self.displays[i].clone());
-                self.should_quit = true;
+        match self.view {
+            TuiView::DisplayList => {
+                // Nothing to choose between with a single mode
+                if self.modes().len() > 1 {
+                    // Start on the native mode, wherever the kernel lists it
+                    let native = self.highlighted_display()
+                        .and_then(|d| d.native_mode)
+                        .and_then(|mode| self.modes().iter().position(|&m| m == mode));
+                    self.view = TuiView::ModeList;
+                    self.mode_state.select(Some(native.unwrap_or(0)));
+                } else {
+                    self.pick(None);
+                }
+            }
+            TuiView::ModeList => {
+                let mode = self.mode_state.selected().and_then(|i| self.modes().get(i).copied());
+                self.pick(mode);
+            }
+            TuiView::RefreshList => {
+                self.refresh_rate = self.refresh_state.selected().and_then(|i| self.refresh_rates().get(i).copied());
+                // Probe again so the confirmation view shows the picked rate
+                self.capabilities = None;
+                self.confirm_or_quit();
+            }
+            TuiView::Confirm => self.should_quit = true,
+        }
+    }
+
+    /// Leave the refresh rate list when the probe found fewer than two
+    /// rates, as if --pick-refresh wasn't given
+    fn skip_refresh_list(&mut self) {
+        self.view = self.picked_from;
+        self.confirm_or_quit();
+    }
+
+    /// Pick whatever is highlighted without drilling into the mode list
+    fn auto_select(&mut self) {
+        match self.view {
+            TuiView::DisplayList => self.pick(None),
+            TuiView::ModeList | TuiView::RefreshList | TuiView::Confirm => self.select(),
+        }
+    }
+
+    /// Go back one view, or quit if already at the display list
+    fn back(&mut self) {
+        match self.view {
+            TuiView::DisplayList => self.quit(),
+            TuiView::ModeList => self.view = TuiView::DisplayList,
+            TuiView::RefreshList => {
+                self.view = self.picked_from;
+                self.selected_display = None;
+                self.capabilities = None;
+            }
+            TuiView::Confirm => {
+                self.view = self.confirm_from;
+                // Back in the refresh rate list the display stays picked
+                if self.view != TuiView::RefreshList {
+                    self.selected_display = None;
+                }
+                self.refresh_rate = None;
+                self.capabilities = None;
             }
         }
     }
+
+    /// Quit without launching anything
+    fn quit(&mut self) {
+        self.selected_display = None;
+        self.should_quit = true;
+    }
+
+    /// Finish with the highlighted display, at `mode` if one was picked
+    fn pick(&mut self, mode: Option<(u32, u32)>) {
+        let Some(mut display) = self.highlighted_display().cloned() else {
+            return;
+        };
+        if let Some((width, height)) = mode {
+            display.resolution = format!("{}x{}", width, height);
+            display.width = width;
+            display.height = height;
+        }
+        self.selected_display = Some(display);
+        self.mode_picked = mode.is_some();
+        self.picked_from = self.view;
+        if self.pick_refresh {
+            self.view = TuiView::RefreshList;
+            self.refresh_state.select(Some(0));
+        } else {
+            self.confirm_or_quit();
+        }
+    }
+
+    /// Show the confirmation view, or finish if it's turned off
+    fn confirm_or_quit(&mut self) {
+        if self.confirm {
+            self.confirm_from = self.view;
+            self.view = TuiView::Confirm;
+        } else {
+            self.should_quit = true;
+        }
+    }
 }
 
-/// Find gamepad devices in /dev/input
-fn find_gamepad_devices() -> Vec<PathBuf> {
+/// Input devices already found not to be gamepads, by path, with the node's
+/// change time: a node recreated for a newly plugged device has a new one and
+/// gets probed again. Shared by every scan in the process, so rescans (while
+/// waiting for a controller, or for one to reconnect) only open new devices.
+static NOT_GAMEPADS: Mutex<Option<HashMap<PathBuf, (i64, i64)>>> = Mutex::new(None);
+
+/// Open the gamepads in /dev/input. The handles are returned rather than the
+/// paths, so nothing opens the same device twice. If none were found and
+/// some devices couldn't be opened for lack of permission, that's logged,
+/// and also printed with `warn_on_stderr` (see [`warn_input_permission_denied`]).
+fn open_gamepads(warn_on_stderr: bool) -> Vec<(PathBuf, Device)> {
     let mut devices = Vec::new();
+    let mut denied = 0;
     let input_path = Path::new("/dev/input");
 
     debug_log("Scanning for gamepad devices in /dev/input...");
@@ -830,34 +1758,52 @@ fn find_gamepad_devices() -> Vec<PathBuf> {
         // Sort entries to process in order
         entries_vec.sort_by_key(|e| e.path());
 
+        let mut not_gamepads = NOT_GAMEPADS.lock().unwrap_or_else(|e| e.into_inner());
+        let not_gamepads = not_gamepads.get_or_insert_with(HashMap::new);
+
         for entry in entries_vec {
             let path = entry.path();
-            if let Some(name) = path.file_name() {
-                let name_str = name.to_string_lossy();
-                if name_str.starts_with("event") {
-                    // Check if we can open it and if it's a gamepad
-                    match Device::open(&path) {
-                        Ok(device) => {
-                            let dev_name = device.name().unwrap_or("unknown");
-                            debug_log(&format!("Opened {}: '{}'", path.display(), dev_name));
-
-                            // Check for gamepad-like keys (BTN_SOUTH is common on gamepads)
-                            if let Some(keys) = device.supported_keys() {
-                                let has_south = keys.contains(Key::BTN_SOUTH);
-                                let has_east = keys.contains(Key::BTN_EAST);
-                                debug_log(&format!("  Keys: BTN_SOUTH={}, BTN_EAST={}", has_south, has_east));
-
-                                if has_south || has_east {
-                                    debug_log(&format!("  -> GAMEPAD DETECTED: {}", dev_name));
-                                    devices.push(path);
-                                }
-                            } else {
-                                debug_log("  No supported_keys()");
-                            }
+            if !entry.file_name().to_string_lossy().starts_with("event") {
+                continue;
+            }
+
+            let changed = entry.metadata().map(|m| (m.ctime(), m.ctime_nsec())).unwrap_or_default();
+            if not_gamepads.get(&path) == Some(&changed) {
+                continue;
+            }
+
+            // Check if we can open it and if it's a gamepad
+            match Device::open(&path) {
+                Ok(device) => {
+                    let dev_name = device.name().unwrap_or("unknown").to_string();
+                    debug_log(&format!("Opened {}: '{}'", path.display(), dev_name));
+
+                    // Check for gamepad-like keys (BTN_SOUTH is common on gamepads)
+                    let is_gamepad = match device.supported_keys() {
+                        Some(keys) => {
+                            let has_south = keys.contains(Key::BTN_SOUTH);
+                            let has_east = keys.contains(Key::BTN_EAST);
+                            debug_log(&format!("  Keys: BTN_SOUTH={}, BTN_EAST={}", has_south, has_east));
+                            has_south || has_east
                         }
-                        Err(e) => {
-                            debug_log(&format!("Cannot open {}: {}", path.display(), e));
+                        None => {
+                            debug_log("  No supported_keys()");
+                            false
                         }
+                    };
+
+                    if is_gamepad {
+                        debug_log(&format!("  -> GAMEPAD DETECTED: {}", dev_name));
+                        not_gamepads.remove(&path);
+                        devices.push((path, device));
+                    } else {
+                        not_gamepads.insert(path, changed);
+                    }
+                }
+                Err(e) => {
+                    debug_log(&format!("Cannot open {}: {}", path.display(), e));
+                    if e.kind() == io::ErrorKind::PermissionDenied {
+                        denied += 1;
                     }
                 }
             }
@@ -866,42 +1812,59 @@ fn find_gamepad_devices() -> Vec<PathBuf> {
         debug_log("Failed to read /dev/input directory");
     }
 
+    if devices.is_empty() && denied > 0 {
+        warn_input_permission_denied(denied, warn_on_stderr);
+    }
+
     debug_log(&format!("Total gamepads found: {}", devices.len()));
     devices
 }
 
-/// Spawn a thread to read controller input
-fn spawn_controller_reader(tx: mpsc::Sender<InputEvent>) {
+/// Explain, once, that controllers are invisible because /dev/input can't
+/// be read. Always logged; also printed with `to_stderr`, which is only
+/// safe before a TUI takes over the terminal.
+fn warn_input_permission_denied(denied: usize, to_stderr: bool) {
+    static WARNED: Once = Once::new();
+    WARNED.call_once(|| {
+        let message = format!(
+            "No controllers found, and permission was denied opening {} input device(s) in /dev/input. \
+             Add yourself to the input group (sudo usermod -aG input $USER, then log in again) or run with access to /dev/input",
+            denied
+        );
+        debug_log(&message);
+        if to_stderr {
+            eprintln!("⚠ {}", message);
+            pause(Duration::from_secs(3));
+        }
+    });
+}
+
+/// Button map for the TUIs: --controller-layout or the config's layout, with
+/// the config's per-button overrides
+fn controller_buttons(args: &Args, config: &Config) -> Result<HashMap<Key, ButtonAction>> {
+    let layout = args.controller_layout
+        .or(config.controller.layout)
+        .unwrap_or_default();
+    button_map(layout, &config.controller.buttons)
+        .context("Invalid [controller] config")
+}
+
+/// Spawn a thread to read controller input from every connected gamepad,
+/// starting with `gamepads` from a scan done before the TUI took over
+fn spawn_controller_reader(tx: mpsc::Sender<InputEvent>, buttons: HashMap<Key, ButtonAction>, rumble: bool, gamepads: Vec<(PathBuf, Device)>) {
     thread::spawn(move || {
         debug_log("Controller reader thread started");
 
-        // Retry loop - keep scanning for controllers until one is found
-        // This handles the case where TUI starts before Bluetooth controller connects
-        let mut device: Device;
+        // Use every gamepad found, since the first one may be an idle
+        // dongle or virtual device rather than the controller in use
+        let mut devices: Vec<Device> = gamepads.into_iter().map(|(_, device)| device).collect();
         let mut retry_count = 0;
         const RETRY_INTERVAL_SECS: u64 = 2;
         const MAX_RETRIES: u32 = 60; // Try for 2 minutes
 
-        loop {
-            let device_paths = find_gamepad_devices();
-
-            if !device_paths.is_empty() {
-                // Open the first gamepad found
-                let device_path = &device_paths[0];
-                debug_log(&format!("Opening gamepad at: {}", device_path.display()));
-
-                match Device::open(device_path) {
-                    Ok(d) => {
-                        debug_log(&format!("Successfully opened: {}", d.name().unwrap_or("unknown")));
-                        device = d;
-                        break;
-                    }
-                    Err(e) => {
-                        debug_log(&format!("Failed to open device: {}", e));
-                    }
-                }
-            }
-
+        // Retry loop - keep scanning for controllers until at least one is found
+        // This handles the case where TUI starts before Bluetooth controller connects
+        while devices.is_empty() {
             retry_count += 1;
             if retry_count >= MAX_RETRIES {
                 debug_log(&format!("No gamepads found after {} retries, giving up", MAX_RETRIES));
@@ -915,96 +1878,239 @@ fn spawn_controller_reader(tx: mpsc::Sender<InputEvent>) {
             }
 
             thread::sleep(Duration::from_secs(RETRY_INTERVAL_SECS));
+            devices = open_gamepads(false).into_iter().map(|(_, device)| device).collect();
         }
 
-        debug_log("Starting event loop...");
-        let mut event_count = 0;
+        // One reader per gamepad, all feeding the same channel. Each exits
+        // once the receiving side is dropped.
+        debug_log(&format!("Starting event loops for {} gamepad(s)...", devices.len()));
+        for device in devices {
+            let tx = tx.clone();
+            let buttons = buttons.clone();
+            thread::spawn(move || run_controller_reader(device, tx, &buttons, rumble));
+        }
+    });
+}
 
-        loop {
-            match device.fetch_events() {
-                Ok(events) => {
-                    for ev in events {
-                        event_count += 1;
+/// Why a controller reader stopped reading
+enum ReaderEnd {
+    /// The TUI is gone
+    ChannelClosed,
+    /// The gamepad was unplugged (or keeps failing to read)
+    Disconnected,
+}
 
-                        // Log every event for debugging
-                        if event_count <= 50 {
-                            debug_log(&format!("Event #{}: type={:?}, code={:?}, value={}",
-                                event_count, ev.kind(), ev.code(), ev.value()));
-                        }
+/// Failed reads in a row after which a gamepad counts as gone, for errors
+/// other than ENODEV
+const MAX_FETCH_ERRORS: u32 = 20;
+
+/// Whether a failed read means the gamepad is gone: ENODEV, or too many
+/// failures in a row
+fn fetch_error_is_disconnect(device_name: &str, error: &io::Error, consecutive_errors: &mut u32) -> bool {
+    *consecutive_errors += 1;
+    if error.raw_os_error() == Some(libc::ENODEV) {
+        debug_log(&format!("{}: disconnected", device_name));
+        return true;
+    }
+    debug_log(&format!("{}: error fetching events: {}", device_name, error));
+    if *consecutive_errors >= MAX_FETCH_ERRORS {
+        debug_log(&format!("{}: {} read errors in a row, treating as disconnected", device_name, consecutive_errors));
+        return true;
+    }
+    false
+}
 
-                        if let InputEventKind::Key(key) = ev.kind() {
-                            debug_log(&format!("Key event: {:?}, value={}", key, ev.value()));
+/// Wait for an unplugged gamepad to come back, matched by its unique ID
+/// (the Bluetooth address) or by name if it has none. Scans with backoff
+/// from 250ms up to 8s between scans, and gives up after 2 minutes.
+fn reopen_gamepad(name: &str, unique_name: Option<&str>) -> Option<Device> {
+    const FIRST_DELAY: Duration = Duration::from_millis(250);
+    const MAX_DELAY: Duration = Duration::from_secs(8);
+    const GIVE_UP_AFTER: Duration = Duration::from_secs(120);
+
+    let started = Instant::now();
+    let mut delay = FIRST_DELAY;
+    while started.elapsed() < GIVE_UP_AFTER {
+        thread::sleep(delay);
+        for (path, device) in open_gamepads(false) {
+            let same = match unique_name {
+                Some(unique_name) => device.unique_name() == Some(unique_name),
+                None => device.name() == Some(name),
+            };
+            if same {
+                debug_log(&format!("{}: reconnected at {}", name, path.display()));
+                return Some(device);
+            }
+        }
+        delay = (delay * 2).min(MAX_DELAY);
+    }
 
-                            // Only process key press events (value == 1)
-                            if ev.value() == 1 {
-                                let input = match key {
-                                    // D-pad
-                                    Key::BTN_DPAD_UP => {
-                                        debug_log("D-pad UP pressed");
-                                        Some(InputEvent::Up)
-                                    }
-                                    Key::BTN_DPAD_DOWN => {
-                                        debug_log("D-pad DOWN pressed");
-                                        Some(InputEvent::Down)
-                                    }
-                                    // Face buttons (BTN_SOUTH = A/Cross, BTN_WEST = X/Square, BTN_EAST = B/Circle)
-                                    Key::BTN_SOUTH => {
-                                        debug_log("BTN_SOUTH (Cross/A) pressed -> Select");
-                                        Some(InputEvent::Select)
-                                    }
-                                    Key::BTN_WEST => {
-                                        debug_log("BTN_WEST (Square/X) pressed -> Select");
-                                        Some(InputEvent::Select)
-                                    }
-                                    Key::BTN_EAST => {
-                                        debug_log("BTN_EAST (Circle/B) pressed -> Quit");
-                                        Some(InputEvent::Quit)
-                                    }
-                                    _ => None,
-                                };
+    debug_log(&format!("{}: did not come back within {}s, stopping its reader", name, GIVE_UP_AFTER.as_secs()));
+    None
+}
 
-                                if let Some(input) = input {
-                                    debug_log("Sending input event to TUI...");
-                                    if tx.send(input).is_err() {
-                                        debug_log("Channel closed, exiting controller reader");
-                                        return;
-                                    }
-                                    debug_log("Input event sent successfully");
+/// Read one gamepad for the TUI until the TUI is gone, reopening it if it's
+/// unplugged and plugged back in
+fn run_controller_reader(mut device: Device, tx: mpsc::Sender<InputEvent>, buttons: &HashMap<Key, ButtonAction>, rumble: bool) {
+    let name = device.name().unwrap_or("unknown").to_string();
+    let unique_name = device.unique_name().filter(|uniq| !uniq.is_empty()).map(str::to_string);
+
+    while let ReaderEnd::Disconnected = read_controller_events(&mut device, &tx, buttons, rumble) {
+        match reopen_gamepad(&name, unique_name.as_deref()) {
+            Some(reopened) => device = reopened,
+            None => return,
+        }
+    }
+}
+
+/// Translate one gamepad's events into navigation input until the channel closes
+fn read_controller_events(device: &mut Device, tx: &mpsc::Sender<InputEvent>, buttons: &HashMap<Key, ButtonAction>, rumble: bool) -> ReaderEnd {
+    // Analog stick deflection (out of ±32767) needed to count as a push
+    const STICK_DEADZONE: i32 = 16000;
+
+    let device_name = device.name().unwrap_or("unknown").to_string();
+    let mut select_rumble = if rumble { upload_select_rumble(device) } else { None };
+    let mut event_count = 0;
+    let mut consecutive_errors = 0;
+    // Last left stick direction (-1 up, 0 centered, 1 down)
+    let mut stick_direction = 0;
+
+    loop {
+        match device.fetch_events() {
+            Ok(events) => {
+                consecutive_errors = 0;
+                for ev in events {
+                    event_count += 1;
+
+                    // Log every event for debugging
+                    if event_count <= 50 {
+                        debug_log(&format!("{} event #{}: type={:?}, code={:?}, value={}",
+                            device_name, event_count, ev.kind(), ev.code(), ev.value()));
+                    }
+
+                    if let InputEventKind::Key(key) = ev.kind() {
+                        debug_log(&format!("Key event: {:?}, value={}", key, ev.value()));
+
+                        // Only process key press events (value == 1)
+                        if ev.value() == 1 {
+                            // D-pad and face buttons, per --controller-layout and [controller]
+                            let input = buttons.get(&key).map(|&action| {
+                                debug_log(&format!("{:?} pressed -> {:?}", key, action));
+                                match action {
+                                    ButtonAction::Up => InputEvent::Up,
+                                    ButtonAction::Down => InputEvent::Down,
+                                    ButtonAction::Select => InputEvent::Select,
+                                    ButtonAction::Back => InputEvent::Quit,
+                                    ButtonAction::ToggleVrr => InputEvent::ToggleVrr,
+                                    ButtonAction::ToggleHdr => InputEvent::ToggleHdr,
+                                }
+                            });
+
+                            if let (Some(InputEvent::Select), Some(effect)) = (&input, &mut select_rumble) {
+                                if let Err(e) = effect.play(1) {
+                                    debug_log(&format!("{}: rumble failed: {}", device_name, e));
                                 }
                             }
+
+                            if let Some(input) = input {
+                                debug_log("Sending input event to TUI...");
+                                if tx.send(input).is_err() {
+                                    debug_log("Channel closed, exiting controller reader");
+                                    return ReaderEnd::ChannelClosed;
+                                }
+                                debug_log("Input event sent successfully");
+                            }
                         }
+                    }
 
-                        // Handle D-pad as absolute axis (HAT)
-                        if let InputEventKind::AbsAxis(axis) = ev.kind() {
-                            use evdev::AbsoluteAxisType;
-                            match axis {
-                                AbsoluteAxisType::ABS_HAT0Y => {
-                                    debug_log(&format!("ABS_HAT0Y: value={}", ev.value()));
-                                    let input = if ev.value() < 0 {
-                                        debug_log("HAT UP -> Navigation Up");
-                                        Some(InputEvent::Up)
-                                    } else if ev.value() > 0 {
-                                        debug_log("HAT DOWN -> Navigation Down");
-                                        Some(InputEvent::Down)
-                                    } else {
-                                        None
-                                    };
-                                    if let Some(input) = input {
-                                        let _ = tx.send(input);
+                    if let InputEventKind::AbsAxis(axis) = ev.kind() {
+                        use evdev::AbsoluteAxisType;
+                        let input = match axis {
+                            // D-pad reported as absolute axis (HAT)
+                            AbsoluteAxisType::ABS_HAT0Y => {
+                                debug_log(&format!("ABS_HAT0Y: value={}", ev.value()));
+                                if ev.value() < 0 {
+                                    debug_log("HAT UP -> Navigation Up");
+                                    Some(InputEvent::Up)
+                                } else if ev.value() > 0 {
+                                    debug_log("HAT DOWN -> Navigation Down");
+                                    Some(InputEvent::Down)
+                                } else {
+                                    None
+                                }
+                            }
+                            // Left analog stick, only acting when it leaves the
+                            // deadzone so holding it doesn't flood navigation
+                            AbsoluteAxisType::ABS_Y => {
+                                let direction = if ev.value() < -STICK_DEADZONE {
+                                    -1
+                                } else if ev.value() > STICK_DEADZONE {
+                                    1
+                                } else {
+                                    0
+                                };
+
+                                if direction == stick_direction {
+                                    None
+                                } else {
+                                    stick_direction = direction;
+                                    match direction {
+                                        -1 => {
+                                            debug_log("Stick UP -> Navigation Up");
+                                            Some(InputEvent::Up)
+                                        }
+                                        1 => {
+                                            debug_log("Stick DOWN -> Navigation Down");
+                                            Some(InputEvent::Down)
+                                        }
+                                        _ => None,
                                     }
                                 }
-                                _ => {}
+                            }
+                            _ => None,
+                        };
+
+                        if let Some(input) = input {
+                            if tx.send(input).is_err() {
+                                debug_log("Channel closed, exiting controller reader");
+                                return ReaderEnd::ChannelClosed;
                             }
                         }
                     }
                 }
-                Err(e) => {
-                    debug_log(&format!("Error fetching events: {}", e));
-                    thread::sleep(Duration::from_millis(100));
+            }
+            Err(e) => {
+                if fetch_error_is_disconnect(&device_name, &e, &mut consecutive_errors) {
+                    return ReaderEnd::Disconnected;
                 }
+                thread::sleep(Duration::from_millis(100));
             }
         }
-    });
+    }
+}
+
+/// Upload the short rumble played on select. Returns `None`, leaving the
+/// controller silent, if it has no rumble motor or wasn't opened writable.
+fn upload_select_rumble(device: &mut Device) -> Option<FFEffect> {
+    let name = device.name().unwrap_or("unknown").to_string();
+    if !device.supported_ff().is_some_and(|ff| ff.contains(FFEffectType::FF_RUMBLE)) {
+        debug_log(&format!("{}: no rumble support", name));
+        return None;
+    }
+
+    let effect = FFEffectData {
+        direction: 0,
+        trigger: FFTrigger { button: 0, interval: 0 },
+        replay: FFReplay { length: 120, delay: 0 },
+        kind: FFEffectKind::Rumble { strong_magnitude: 0x6000, weak_magnitude: 0xa000 },
+    };
+    match device.upload_ff_effect(effect) {
+        Ok(effect) => Some(effect),
+        Err(e) => {
+            debug_log(&format!("{}: could not upload rumble effect: {}", name, e));
+            None
+        }
+    }
 }
 
 /// Render the TUI
@@ -1014,51 +2120,153 @@ fn render_tui(frame: &mut Frame, app: &mut TuiApp) {
     // Create a centered box
     let popup_area = centered_rect(60, 60, area);
 
+    if app.view == TuiView::Confirm {
+        render_confirm(frame, app, popup_area);
+        return;
+    }
+
     // Create the list items
-    let items: Vec<ListItem> = app
-        .displays
-        .iter()
-        .map(|d| {
-            let content = format!("{} ({})", d.connector_name, d.resolution);
-            ListItem::new(Line::from(content))
-        })
-        .collect();
+    let (title, items): (String, Vec<ListItem>) = match app.view {
+        TuiView::DisplayList => {
+            let items = app
+                .filtered_displays()
+                .map(|d| {
+                    let mut spans = vec![Span::raw(format!("{} ({})", d.connector_name, d.resolution))];
+                    if let Some(probed) = &app.probed {
+                        spans.extend(capability_badges(probed.get(&d.connector_name)));
+                    }
+                    ListItem::new(Line::from(spans))
+                })
+                .collect();
+            let title = if app.filter.is_empty() {
+                " Console Mode - Select Monitor ".to_string()
+            } else {
+                format!(" Console Mode - Select Monitor [/{}] ", app.filter)
+            };
+            (title, items)
+        }
+        TuiView::ModeList => {
+            let name = app.highlighted_display().map_or("", |d| d.connector_name.as_str());
+            let native_mode = app.highlighted_display().and_then(|d| d.native_mode);
+            let items = app
+                .modes()
+                .iter()
+                .map(|&(width, height)| {
+                    let content = if native_mode == Some((width, height)) {
+                        format!("{}x{} (native)", width, height)
+                    } else {
+                        format!("{}x{}", width, height)
+                    };
+                    ListItem::new(Line::from(content))
+                })
+                .collect();
+            (format!(" {} - Select Resolution ", name), items)
+        }
+        TuiView::RefreshList => {
+            let resolution = app.selected_display.as_ref().map_or("", |d| d.resolution.as_str());
+            let items = app
+                .refresh_rates()
+                .iter()
+                .map(|rate| ListItem::new(Line::from(format!("{}Hz", rate))))
+                .collect();
+            (format!(" {} - Select Refresh Rate ", resolution), items)
+        }
+        TuiView::Confirm => unreachable!("rendered by render_confirm"),
+    };
+
+    // Keep the highlighted row inside the popup (minus its borders)
+    let visible_rows = usize::from(popup_area.height.saturating_sub(2));
+    app.visible_rows = visible_rows;
+    app.scroll_to_selection();
+
+    let (len, list_state) = app.active_list();
+    let overflows = len > visible_rows;
+    let selected = list_state.selected().unwrap_or(0);
+
+    // Show where we are when the list doesn't fit, e.g. "(3/12)"
+    let title = if overflows {
+        format!("{}({}/{}) ", title, selected + 1, len)
+    } else {
+        title
+    };
 
     // Create the list widget
     let list = List::new(items)
         .block(
             Block::default()
-                .title(" Console Mode - Select Monitor ")
+                .title(title)
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
+                .border_style(Style::default().fg(theme().border)),
         )
         .highlight_style(
             Style::default()
-                .fg(Color::Black)
-                .bg(Color::Cyan)
+                .fg(theme().highlight_fg)
+                .bg(theme().highlight_bg)
                 .add_modifier(Modifier::BOLD),
         )
         .highlight_symbol("▶ ");
 
-    frame.render_stateful_widget(list, popup_area, &mut app.list_state);
+    frame.render_stateful_widget(list, popup_area, list_state);
+
+    if overflows {
+        let mut scrollbar_state = ScrollbarState::new(len).position(selected);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None),
+            popup_area.inner(Margin::new(0, 1)),
+            &mut scrollbar_state,
+        );
+    }
 
     // Render help text at the bottom
     let help_area = Rect {
         x: popup_area.x,
         y: popup_area.y + popup_area.height,
         width: popup_area.width,
-        height: 2,
+        height: 3,
     };
 
     if help_area.y + help_area.height <= area.height {
-        let help_text = Paragraph::new(Line::from(vec![
-            Span::styled("[↑/↓] ", Style::default().fg(Color::Yellow)),
-            Span::raw("Navigate  "),
-            Span::styled("[Enter/A] ", Style::default().fg(Color::Green)),
-            Span::raw("Select  "),
-            Span::styled("[Esc/B] ", Style::default().fg(Color::Red)),
-            Span::raw("Quit"),
-        ]));
+        let mut help_lines = if app.filtering {
+            vec![Line::from(vec![
+                Span::styled("Filter: ", Style::default().fg(theme().navigate_key)),
+                Span::raw(format!("{}▏  ", app.filter)),
+                Span::styled("[Enter] ", Style::default().fg(theme().select_key)),
+                Span::raw("Done  "),
+                Span::styled("[Esc] ", Style::default().fg(theme().back_key)),
+                Span::raw("Clear"),
+            ])]
+        } else {
+            let mut spans = vec![
+                Span::styled("[↑/↓] ", Style::default().fg(theme().navigate_key)),
+                Span::raw("Navigate  "),
+                Span::styled("[Enter/A] ", Style::default().fg(theme().select_key)),
+                Span::raw("Select  "),
+                Span::styled("[Esc/B] ", Style::default().fg(theme().back_key)),
+                Span::raw(match app.view {
+                    TuiView::DisplayList if !app.filter.is_empty() => "Clear filter",
+                    TuiView::DisplayList => "Quit",
+                    _ => "Back",
+                }),
+            ];
+            if app.view == TuiView::DisplayList {
+                spans.push(Span::styled("  [/] ", Style::default().fg(theme().navigate_key)));
+                spans.push(Span::raw("Filter"));
+            }
+            vec![Line::from(spans), override_line(app)]
+        };
+
+        if let Some(remaining) = app.auto_select_remaining() {
+            // Round up so the countdown reaches 0 exactly when selection happens
+            let secs = remaining.as_millis().div_ceil(1000);
+            help_lines.push(Line::from(Span::styled(
+                format!("Auto-selecting in {}s…", secs),
+                Style::default().fg(theme().hint),
+            )));
+        }
+
+        let help_text = Paragraph::new(help_lines);
         frame.render_widget(help_text, help_area);
     }
 
@@ -1070,6 +2278,156 @@ fn render_tui(frame: &mut Frame, app: &mut TuiApp) {
     }
 }
 
+/// Cycle an override: auto, on, off, auto
+fn next_override(value: Option<bool>) -> Option<bool> {
+    match value {
+        None => Some(true),
+        Some(true) => Some(false),
+        Some(false) => None,
+    }
+}
+
+/// The CLI flags as an override, `None` if neither was given
+fn flag_override(force: bool, disable: bool) -> Option<bool> {
+    if force {
+        Some(true)
+    } else if disable {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Footer line with the VRR/HDR overrides, e.g. "[VRR:on] [HDR:auto]"
+fn override_line(app: &TuiApp) -> Line<'static> {
+    let tag = |name: &str, value: Option<bool>| {
+        let (text, color) = match value {
+            Some(true) => ("on", theme().good),
+            Some(false) => ("off", theme().bad),
+            None => ("auto", theme().hint),
+        };
+        Span::styled(format!("[{}:{}] ", name, text), Style::default().fg(color))
+    };
+
+    Line::from(vec![
+        tag("VRR", app.vrr),
+        tag("HDR", app.hdr),
+        Span::styled(" [V/LB] [H/RB] ", Style::default().fg(theme().navigate_key)),
+        Span::raw("Toggle"),
+    ])
+}
+
+/// Tags after a display in the list for --tui-detect-all: VRR, HDR and
+/// refresh rate, or a placeholder while detection is still running
+fn capability_badges(probed: Option<&ProbeResult>) -> Vec<Span<'static>> {
+    let badge = |text: String, color: Color| Span::styled(format!(" [{}]", text), Style::default().fg(color));
+
+    match probed {
+        None => vec![badge("…".to_string(), theme().hint)],
+        Some(Err(_)) => vec![badge("?".to_string(), theme().bad)],
+        Some(Ok((caps, warnings))) => {
+            let mut badges = Vec::new();
+            if caps.vrr {
+                badges.push(badge("VRR".to_string(), theme().good));
+            }
+            if caps.hdr {
+                badges.push(badge("HDR".to_string(), theme().hdr_badge));
+            }
+            badges.push(badge(format!("{}Hz", caps.max_refresh_rate), theme().refresh_badge));
+            if !warnings.is_empty() {
+                badges.push(badge("⚠".to_string(), theme().warning));
+            }
+            badges
+        }
+    }
+}
+
+/// Render the confirmation view: what was picked and what was detected
+fn render_confirm(frame: &mut Frame, app: &TuiApp, popup_area: Rect) {
+    let area = frame.area();
+    let Some(display) = &app.selected_display else {
+        return;
+    };
+
+    let yes_no = |supported: bool| {
+        if supported {
+            Span::styled("✓ ", Style::default().fg(theme().good))
+        } else {
+            Span::styled("✗ ", Style::default().fg(theme().bad))
+        }
+    };
+
+    let mut lines = vec![
+        Line::from(format!("Resolution: {}", display.resolution)),
+        Line::from(""),
+    ];
+
+    match &app.capabilities {
+        None => lines.push(Line::from("Detecting capabilities…")),
+        Some(Err(e)) => lines.push(Line::from(Span::styled(
+            format!("Capability detection failed: {:#}", e),
+            Style::default().fg(theme().bad),
+        ))),
+        Some(Ok((caps, warnings))) => {
+            let vrr = match (caps.vrr_min, caps.vrr_max) {
+                (Some(min), Some(max)) if caps.vrr => format!("VRR/Adaptive Sync ({}-{}Hz)", min, max),
+                _ => "VRR/Adaptive Sync".to_string(),
+            };
+            lines.push(Line::from(vec![yes_no(caps.vrr), Span::raw(vrr)]));
+            lines.push(Line::from(vec![yes_no(caps.hdr), Span::raw("HDR")]));
+            lines.push(Line::from(format!("Refresh rate: {}Hz", caps.max_refresh_rate)));
+            lines.push(Line::from(format!("Color depth: {}-bit", caps.max_bpc)));
+
+            if !warnings.is_empty() {
+                lines.push(Line::from(""));
+            }
+            for warning in warnings {
+                lines.push(Line::from(Span::styled(
+                    format!("⚠ {}", warning),
+                    Style::default().fg(theme().warning),
+                )));
+            }
+        }
+    }
+
+    let details = Paragraph::new(lines).block(
+        Block::default()
+            .title(format!(" {} - Confirm Launch ", display.connector_name))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme().border)),
+    );
+    frame.render_widget(details, popup_area);
+
+    let help_area = Rect {
+        x: popup_area.x,
+        y: popup_area.y + popup_area.height,
+        width: popup_area.width,
+        height: 3,
+    };
+
+    if help_area.y + help_area.height <= area.height {
+        let mut help_lines = vec![
+            Line::from(vec![
+                Span::styled("[Enter/A] ", Style::default().fg(theme().select_key)),
+                Span::raw("Confirm  "),
+                Span::styled("[Esc/B] ", Style::default().fg(theme().back_key)),
+                Span::raw("Back"),
+            ]),
+            override_line(app),
+        ];
+
+        if let Some(remaining) = app.auto_select_remaining() {
+            let secs = remaining.as_millis().div_ceil(1000);
+            help_lines.push(Line::from(Span::styled(
+                format!("Launching in {}s…", secs),
+                Style::default().fg(theme().hint),
+            )));
+        }
+
+        frame.render_widget(Paragraph::new(help_lines), help_area);
+    }
+}
+
 /// Helper to create a centered rectangle
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::vertical([
@@ -1087,51 +2445,170 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     .split(popup_layout[1])[1]
 }
 
-/// Run the TUI launcher
-fn run_tui_launcher(args: Args) -> Result<()> {
+/// Keeps the terminal in raw mode on the alternate screen while alive.
+/// Dropping it restores the terminal, so early `?` returns and panics
+/// don't leave the user's shell garbled.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> Result<Self> {
+        install_panic_hook();
+        enable_raw_mode()?;
+        let guard = TerminalGuard;
+        io::stdout().execute(EnterAlternateScreen)?;
+        signals::set_tui_active(true);
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+        // A SIGTERM that ended the TUI ends us too, now the terminal is back
+        signals::set_tui_active(false);
+        signals::exit_if_terminated();
+    }
+}
+
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = io::stdout().execute(LeaveAlternateScreen);
+}
+
+/// Restore the terminal before the default hook prints a panic message, which
+/// would otherwise be lost on the alternate screen. Panics on the controller
+/// reader threads are left alone, since the TUI keeps running after those.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if thread::current().name() == Some("main") {
+                restore_terminal();
+            }
+            default_hook(info);
+        }));
+    });
+}
+
+/// Probe each display's capabilities on its own thread, with its config
+/// profile applied, sending the results back by connector name
+fn spawn_capability_probes(displays: &[DisplayInfo], args: &Args, config: &Config) -> mpsc::Receiver<(String, ProbeResult)> {
+    let (tx, rx) = mpsc::channel();
+
+    for display in displays {
+        let mut probe_args = args.clone();
+        let applied = apply_display_profile(config, display, &mut probe_args);
+        let display = display.clone();
+        let tx = tx.clone();
+        thread::spawn(move || {
+            let result = applied.and_then(|_| probe_capabilities(&SystemRunner, &display, &probe_args).map_err(anyhow::Error::from));
+            debug_log(&format!("{}: background detection done (ok: {})", display.connector_name, result.is_ok()));
+            let _ = tx.send((display.connector_name, result));
+        });
+    }
+
+    rx
+}
+
+/// Run the TUI launcher. Returns the args the picked display was launched
+/// with, or `None` if the user quit without picking one.
+fn run_tui_launcher(args: Args, config: &Config) -> Result<Option<Args>> {
     // Detect displays first
-    let displays = detect_displays()?;
+    let displays = detect_displays_waiting(&args)?;
 
     // If only one display, skip the TUI and just launch
     if displays.len() == 1 {
-        println!("Single display detected: {} at {}", displays[0].connector_name, displays[0].resolution);
-        thread::sleep(Duration::from_secs(1));
+        info!("Single display detected: {} at {}", displays[0].connector_name, displays[0].resolution);
+        pause(Duration::from_secs(1));
 
         let mut new_args = args;
         new_args.display = Some(displays[0].connector_name.clone());
         new_args.tui_launcher = false;
 
         // Re-run without TUI
-        return launch_with_display(&displays[0], new_args);
+        launch_tui_selection(&displays[0], new_args.clone(), config)?;
+        return Ok(Some(new_args));
     }
 
+    // Resolve controller buttons before taking over the terminal, so a bad
+    // [controller] table is reported normally
+    let buttons = controller_buttons(&args, config)?;
+    // Scan while the terminal is still ours, so finding no gamepads because
+    // /dev/input can't be read is shown rather than only logged
+    let gamepads = open_gamepads(true);
+
     // Set up terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    stdout.execute(EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+    let terminal_guard = TerminalGuard::enter()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state
-    let mut app = TuiApp::new(displays);
+    let timeout = args.tui_timeout
+        .filter(|&secs| secs > 0)
+        .map(Duration::from_secs);
+    // Remembering is on unless --no-remember-display was the last of the pair given
+    let remember_display = args.remember_display || !args.no_remember_display;
+    let last_display = if remember_display {
+        state::load_last_display()
+    } else {
+        None
+    };
+    // Probe every display in the background, since edid-decode can take a
+    // while per display and the list should be usable meanwhile
+    let probe_rx = args.tui_detect_all.then(|| spawn_capability_probes(&displays, &args, config));
+
+    let mut app = TuiApp::new(displays, last_display.as_deref(), timeout, !args.tui_no_confirm);
+    if probe_rx.is_some() {
+        app.probed = Some(HashMap::new());
+    }
+    app.vrr = flag_override(args.force_vrr, args.no_vrr);
+    app.hdr = flag_override(args.force_hdr, args.no_hdr);
+    app.pick_refresh = args.pick_refresh;
 
     // Set up input channel for controller
     let (tx, rx) = mpsc::channel::<InputEvent>();
-    spawn_controller_reader(tx);
+    spawn_controller_reader(tx, buttons, args.tui_rumble, gamepads);
 
     // Main loop
     loop {
+        if let (Some(rx), Some(probed)) = (&probe_rx, &mut app.probed) {
+            while let Ok((name, result)) = rx.try_recv() {
+                probed.insert(name, result);
+            }
+        }
+
+        // Probe the picked display for the refresh rate list and the
+        // confirmation view, with its config profile applied as it will be at launch
+        if matches!(app.view, TuiView::RefreshList | TuiView::Confirm) && app.capabilities.is_none() {
+            if let Some(display) = &app.selected_display {
+                let mut probe_args = args.clone();
+                app.apply_overrides(&mut probe_args);
+                app.capabilities = Some(apply_display_profile(config, display, &mut probe_args).and_then(|_| {
+                    probe_capabilities(&SystemRunner, display, &probe_args).map_err(anyhow::Error::from)
+                }));
+            }
+        }
+
+        if app.view == TuiView::RefreshList && app.capabilities.is_some() && app.refresh_rates().len() < 2 {
+            app.skip_refresh_list();
+            continue;
+        }
+
         // Draw
         terminal.draw(|f| render_tui(f, &mut app))?;
 
         // Handle input
         // Check for controller input (non-blocking)
         if let Ok(input) = rx.try_recv() {
+            app.reset_timeout();
             match input {
                 InputEvent::Up => app.previous(),
                 InputEvent::Down => app.next(),
                 InputEvent::Select => app.select(),
-                InputEvent::Quit => app.should_quit = true,
+                InputEvent::Quit => app.back(),
+                InputEvent::ToggleVrr => app.toggle_vrr(),
+                InputEvent::ToggleHdr => app.toggle_hdr(),
                 InputEvent::AnyButton => {} // Not used in TUI mode
             }
         }
@@ -1140,51 +2617,301 @@ fn run_tui_launcher(args: Args) -> Result<()> {
         if event::poll(Duration::from_millis(50))? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
+                    app.reset_timeout();
                     match key.code {
+                        // Raw mode turns Ctrl+C into a key press instead of SIGINT
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => app.quit(),
+                        // Typing a filter: letters go into it rather than being shortcuts
+                        KeyCode::Up if app.filtering => app.previous(),
+                        KeyCode::Down if app.filtering => app.next(),
+                        KeyCode::Enter if app.filtering => app.filtering = false,
+                        KeyCode::Esc if app.filtering => app.clear_filter(),
+                        KeyCode::Backspace if app.filtering => {
+                            app.filter.pop();
+                            app.apply_filter();
+                        }
+                        KeyCode::Char(c) if app.filtering => {
+                            app.filter.push(c);
+                            app.apply_filter();
+                        }
+                        _ if app.filtering => {}
+                        KeyCode::Char('/') if app.view == TuiView::DisplayList => app.filtering = true,
+                        KeyCode::Esc if app.view == TuiView::DisplayList && !app.filter.is_empty() => app.clear_filter(),
                         KeyCode::Up | KeyCode::Char('k') => app.previous(),
                         KeyCode::Down | KeyCode::Char('j') => app.next(),
                         KeyCode::Enter | KeyCode::Char(' ') => app.select(),
-                        KeyCode::Esc | KeyCode::Char('q') => app.should_quit = true,
+                        KeyCode::Esc | KeyCode::Backspace => app.back(),
+                        KeyCode::Char('q') => app.quit(),
+                        KeyCode::Char('v') => app.toggle_vrr(),
+                        KeyCode::Char('h') => app.toggle_hdr(),
                         _ => {}
                     }
                 }
             }
         }
 
-        if app.should_quit {
+        // Auto-select the highlighted display once the timeout elapses
+        if app.auto_select_remaining() == Some(Duration::ZERO) {
+            debug_log("TUI timeout elapsed, auto-selecting highlighted display");
+            app.auto_select();
+        }
+
+        if app.should_quit || signals::terminated() {
             break;
         }
     }
 
     // Restore terminal
-    disable_raw_mode()?;
-    terminal.backend_mut().execute(LeaveAlternateScreen)?;
+    drop(terminal_guard);
 
     // If a display was selected, launch with it
-    if let Some(display) = app.selected_display {
-        println!("\nLaunching with display: {} at {}", display.connector_name, display.resolution);
-        thread::sleep(Duration::from_secs(1));
+    if let Some(display) = app.selected_display.take() {
+        if remember_display {
+            if let Err(e) = state::save_last_display(&display.connector_name) {
+                debug_log(&format!("Failed to remember selected display: {}", e));
+            }
+        }
+
+        info!("\nLaunching with display: {} at {}", display.connector_name, display.resolution);
+        pause(Duration::from_secs(1));
 
         let mut new_args = args;
         new_args.display = Some(display.connector_name.clone());
         new_args.tui_launcher = false;
+        // The refresh rate, if any, was picked in the TUI
+        new_args.pick_refresh = false;
+        app.apply_overrides(&mut new_args);
+        // A mode picked in the TUI wins over the config profile's resolution
+        if app.mode_picked {
+            new_args.resolution = Some(display.resolution.clone());
+        }
 
-        launch_with_display(&display, new_args)?;
+        launch_tui_selection(&display, new_args.clone(), config)?;
+        return Ok(Some(new_args));
     }
 
-    Ok(())
+    Ok(None)
+}
+
+/// Launch on the display picked in the TUI, or in a window when running
+/// inside another compositor, where there's no output to take over
+fn launch_tui_selection(display: &DisplayInfo, args: Args, config: &Config) -> Result<()> {
+    if is_running_nested(&args) {
+        return launch_nested_session(&args);
+    }
+    launch_with_display(display, args, config)
 }
 
 /// Launch gamescope with a specific display
-fn launch_with_display(display: &DisplayInfo, args: Args) -> Result<()> {
+fn launch_with_display(display: &DisplayInfo, mut args: Args, config: &Config) -> Result<()> {
+    // Every selection path (CLI, launcher, TUI) ends up here right after picking
+    if args.cec_poweron {
+        cec_power_on(display, &args);
+    }
+    if args.ddc_poweron || args.ddc_input.is_some() {
+        ddc_prepare(display, &args);
+    }
+
+    // Apply the display's config profile underneath any CLI overrides
+    if apply_display_profile(config, display, &mut args)? {
+        info!("Using config profile for {}", display.connector_name);
+    }
+
+    if let Some(ref path) = args.dump_edid {
+        dump_display_edid(display, path);
+    }
+
+    // A panel mounted on its side (handhelds, some cabinets) says so in DRM
+    if args.rotate.is_none() {
+        if let Some(degrees) = panel_orientation(display).filter(|&degrees| degrees != 0) {
+            info!("{} is mounted rotated {}°, rotating the output to match", display.connector_name, degrees);
+            args.rotate = Some(degrees);
+        }
+    }
+
+    // Override resolution if specified
+    let display = if let Some(ref res) = args.resolution {
+        let (width, height) = parse_resolution(res)?;
+        check_supported_mode(display, (width, height), args.strict)?;
+        DisplayInfo {
+            resolution: res.clone(),
+            width,
+            height,
+            ..display.clone()
+        }
+    } else {
+        display.clone()
+    };
+
+    if let Some(ref res) = args.render_resolution {
+        let (width, height) = parse_resolution(res)?;
+        if width > display.width || height > display.height {
+            anyhow::bail!("--render-resolution {} is larger than the output resolution {}", res, display.resolution);
+        }
+    }
+
     // Detect capabilities for this display
-    println!("\n=== Detecting Display Capabilities ===\n");
-    let capabilities = detect_capabilities(display, &args)?;
-    println!();
-    thread::sleep(Duration::from_secs(2));
+    info!("\n=== Detecting Display Capabilities ===\n");
+    let mut capabilities = detect_capabilities(&SystemRunner, &display, &args)?;
+    info!();
+    if sunshine_client_hdr() == Some(true) && !capabilities.hdr && !args.no_hdr {
+        eprintln!("⚠ The Sunshine client wants HDR, but {} doesn't support it; streaming in SDR", display.connector_name);
+    }
+    if args.pick_refresh {
+        if let Some(rate) = select_refresh_rate_interactive(&display, &capabilities, &args)? {
+            capabilities.max_refresh_rate = rate;
+        }
+    }
+    pause(Duration::from_secs(2));
 
     // Launch gamescope
-    launch_gamescope(display, &capabilities, &args)
+    let context = HookContext {
+        connector: Some(display.connector_name.clone()),
+        width: display.width,
+        height: display.height,
+        refresh_rate: capabilities.max_refresh_rate,
+    };
+    with_launch_hooks(&args, &context, || launch_gamescope(&SystemRunner, &display, &capabilities, &args, config))
+}
+
+/// Run --pre-launch, then the launch, then --post-launch whatever the launch's
+/// outcome (as CONSOLE_MODE_RESULT), even when a signal stopped it; we exit
+/// with the signal after that. A failing pre-launch hook only warns unless
+/// --pre-launch-required. With --dry-run the hooks are only printed.
+fn with_launch_hooks(args: &Args, context: &HookContext, launch: impl FnOnce() -> Result<()>) -> Result<()> {
+    let env = context.env();
+
+    if let Some(ref command) = args.pre_launch {
+        if args.dry_run {
+            println!("Would run pre-launch hook: {}", command);
+        } else {
+            info!("Running pre-launch hook: {}", command);
+            if let Err(e) = run_hook(&SystemRunner, command, &env) {
+                if args.pre_launch_required {
+                    return Err(e.context("Pre-launch hook failed, not launching"));
+                }
+                eprintln!("⚠ Pre-launch hook failed: {:#}", e);
+            }
+        }
+    }
+
+    signals::defer_exit(true);
+    let result = launch();
+    signals::defer_exit(false);
+
+    if let Some(ref command) = args.post_launch {
+        if args.dry_run {
+            println!("Would run post-launch hook: {}", command);
+        } else {
+            let mut env = env;
+            env.push(("CONSOLE_MODE_RESULT", if result.is_ok() { "success" } else { "failure" }.to_string()));
+            info!("Running post-launch hook: {}", command);
+            if let Err(e) = run_hook(&SystemRunner, command, &env) {
+                eprintln!("⚠ Post-launch hook failed: {:#}", e);
+            }
+        }
+    }
+
+    signals::exit_if_terminated();
+    result
+}
+
+/// Wake an HDMI display over CEC; failures only warn, since the display
+/// may well be on already
+#[cfg(feature = "cec")]
+fn cec_power_on(display: &DisplayInfo, args: &Args) {
+    if !display.output_name().starts_with("HDMI") {
+        println!("⚠ {} is not an HDMI output, skipping CEC power on", display.connector_name);
+        return;
+    }
+
+    if args.dry_run {
+        println!("Would power on {} over CEC", display.connector_name);
+        return;
+    }
+
+    info!("Powering on {} over CEC...", display.connector_name);
+    match console_mode::cec::power_on() {
+        // Give the TV a moment to wake and switch inputs
+        Ok(()) => thread::sleep(Duration::from_secs(3)),
+        Err(e) => {
+            println!("⚠ CEC power on failed: {:#}", e);
+            debug_log(&format!("CEC power on failed: {:?}", e));
+        }
+    }
+}
+
+#[cfg(not(feature = "cec"))]
+fn cec_power_on(_display: &DisplayInfo, _args: &Args) {
+    println!("⚠ --cec-poweron needs console-mode built with the `cec` feature");
+}
+
+/// Power the monitor on and/or switch its input over DDC/CI (--ddc-poweron,
+/// --ddc-input); failures only warn, like CEC's
+#[cfg(feature = "ddc")]
+fn ddc_prepare(display: &DisplayInfo, args: &Args) {
+    use console_mode::ddc;
+
+    let Some(bus) = ddc::i2c_bus(&display.connector_path) else {
+        println!("⚠ No DDC/CI bus found for {}, skipping ddcutil", display.connector_name);
+        return;
+    };
+
+    if args.dry_run {
+        if args.ddc_poweron {
+            println!("Would power on {} over DDC/CI (bus {})", display.connector_name, bus);
+        }
+        if let Some(input) = args.ddc_input {
+            println!("Would switch {} to input 0x{:02x} over DDC/CI (bus {})", display.connector_name, input, bus);
+        }
+        return;
+    }
+
+    if args.ddc_poweron {
+        info!("Powering on {} over DDC/CI...", display.connector_name);
+        match ddc::power_on(&SystemRunner, bus) {
+            // Monitors ignore input switches for a moment after waking
+            Ok(()) => thread::sleep(Duration::from_secs(3)),
+            Err(e) => println!("⚠ DDC/CI power on failed: {:#}", e),
+        }
+    }
+
+    if let Some(input) = args.ddc_input {
+        info!("Switching {} to input 0x{:02x} over DDC/CI...", display.connector_name, input);
+        if let Err(e) = ddc::switch_input(&SystemRunner, bus, input) {
+            println!("⚠ DDC/CI input switch failed: {:#}", e);
+        }
+    }
+}
+
+#[cfg(not(feature = "ddc"))]
+fn ddc_prepare(_display: &DisplayInfo, _args: &Args) {
+    println!("⚠ --ddc-poweron and --ddc-input need console-mode built with the `ddc` feature");
+}
+
+/// Warn (or fail with --strict) when a resolution override isn't one of the
+/// display's modes, since gamescope would otherwise fail without saying why
+fn check_supported_mode(display: &DisplayInfo, mode: (u32, u32), strict: bool) -> Result<()> {
+    if display.available_modes.is_empty() || display.available_modes.contains(&mode) {
+        return Ok(());
+    }
+
+    let suggestion = closest_mode(&display.available_modes, mode)
+        .map(|(w, h)| format!("; closest supported mode is {}x{}", w, h))
+        .unwrap_or_default();
+
+    if strict {
+        anyhow::bail!(
+            "{}x{} is not supported by {}{}",
+            mode.0, mode.1, display.connector_name, suggestion
+        );
+    }
+
+    println!(
+        "⚠ {}x{} is not a supported mode of {}{}",
+        mode.0, mode.1, display.connector_name, suggestion
+    );
+    Ok(())
 }
 
 // ============================================================================
@@ -1203,8 +2930,9 @@ fn is_gamescope_running() -> bool {
         .unwrap_or(false)
 }
 
-/// Spawn a controller reader for idle watcher mode (sends AnyButton for any input)
-fn spawn_idle_controller_reader(tx: mpsc::Sender<InputEvent>) {
+/// Spawn a controller reader for idle watcher mode (sends AnyButton for any
+/// input), starting with `gamepads` from a scan done before the TUI took over
+fn spawn_idle_controller_reader(tx: mpsc::Sender<InputEvent>, mut gamepads: Vec<(PathBuf, Device)>) {
     thread::spawn(move || {
         debug_log("Idle controller reader thread started");
 
@@ -1215,23 +2943,10 @@ fn spawn_idle_controller_reader(tx: mpsc::Sender<InputEvent>) {
         const MAX_RETRIES: u32 = 180; // Try for 6 minutes in idle mode
 
         loop {
-            let device_paths = find_gamepad_devices();
-
-            if !device_paths.is_empty() {
-                let device_path = &device_paths[0];
-                debug_log(&format!("Opening gamepad at: {}", device_path.display()));
-
-                match Device::open(device_path) {
-                    Ok(d) => {
-                        debug_log(&format!("Successfully opened: {}", d.name().unwrap_or("unknown")));
-                        device = d;
-                        // Send a message to indicate controller is connected
-                        break;
-                    }
-                    Err(e) => {
-                        debug_log(&format!("Failed to open device: {}", e));
-                    }
-                }
+            if let Some((device_path, d)) = gamepads.into_iter().next() {
+                debug_log(&format!("Using gamepad at {}: {}", device_path.display(), d.name().unwrap_or("unknown")));
+                device = d;
+                break;
             }
 
             retry_count += 1;
@@ -1247,13 +2962,18 @@ fn spawn_idle_controller_reader(tx: mpsc::Sender<InputEvent>) {
             }
 
             thread::sleep(Duration::from_secs(RETRY_INTERVAL_SECS));
+            gamepads = open_gamepads(false);
         }
 
         debug_log("Idle watcher: Starting event loop for any button...");
 
+        let name = device.name().unwrap_or("unknown").to_string();
+        let unique_name = device.unique_name().filter(|uniq| !uniq.is_empty()).map(str::to_string);
+        let mut consecutive_errors = 0;
         loop {
-            match device.fetch_events() {
+            let disconnected = match device.fetch_events() {
                 Ok(events) => {
+                    consecutive_errors = 0;
                     for ev in events {
                         // In idle mode, any key press triggers the TUI
                         if let InputEventKind::Key(_) = ev.kind() {
@@ -1275,11 +2995,19 @@ fn spawn_idle_controller_reader(tx: mpsc::Sender<InputEvent>) {
                             }
                         }
                     }
+                    false
                 }
-                Err(e) => {
-                    debug_log(&format!("Error fetching events: {}", e));
-                    thread::sleep(Duration::from_millis(100));
+                Err(e) => fetch_error_is_disconnect(&name, &e, &mut consecutive_errors),
+            };
+
+            if disconnected {
+                match reopen_gamepad(&name, unique_name.as_deref()) {
+                    Some(reopened) => device = reopened,
+                    None => return,
                 }
+                consecutive_errors = 0;
+            } else if consecutive_errors > 0 {
+                thread::sleep(Duration::from_millis(100));
             }
         }
     });
@@ -1295,13 +3023,13 @@ fn render_idle_watcher(frame: &mut Frame, controller_connected: bool) {
     // Build the content
     let controller_status = if controller_connected {
         Line::from(vec![
-            Span::styled("Controller: ", Style::default().fg(Color::White)),
-            Span::styled("Connected", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::raw("Controller: "),
+            Span::styled("Connected", Style::default().fg(theme().good).add_modifier(Modifier::BOLD)),
         ])
     } else {
         Line::from(vec![
-            Span::styled("Controller: ", Style::default().fg(Color::White)),
-            Span::styled("Waiting...", Style::default().fg(Color::Yellow)),
+            Span::raw("Controller: "),
+            Span::styled("Waiting...", Style::default().fg(theme().warning)),
         ])
     };
 
@@ -1309,7 +3037,7 @@ fn render_idle_watcher(frame: &mut Frame, controller_connected: bool) {
         Line::from(""),
         Line::from(Span::styled(
             "Console Mode",
-            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme().border).add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from("Press any controller button to start local gaming"),
@@ -1318,7 +3046,7 @@ fn render_idle_watcher(frame: &mut Frame, controller_connected: bool) {
         Line::from(""),
         Line::from(Span::styled(
             "Waiting for Sunshine connection...",
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme().hint),
         )),
     ];
 
@@ -1327,7 +3055,7 @@ fn render_idle_watcher(frame: &mut Frame, controller_connected: bool) {
             Block::default()
                 .title(" Idle ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
+                .border_style(Style::default().fg(theme().border)),
         )
         .alignment(Alignment::Center);
 
@@ -1343,34 +3071,37 @@ fn render_idle_watcher(frame: &mut Frame, controller_connected: bool) {
 
     if help_area.y + help_area.height <= area.height {
         let help_text = Paragraph::new(Line::from(vec![
-            Span::styled("[ESC] ", Style::default().fg(Color::Red)),
+            Span::styled("[ESC] ", Style::default().fg(theme().back_key)),
             Span::raw("Quit to shell"),
         ]));
         frame.render_widget(help_text, help_area);
     }
 }
 
-/// Run the idle watcher - waits for controller input or Sunshine session
-fn run_idle_watcher(args: Args) -> Result<()> {
+/// Run the idle watcher - waits for controller input or Sunshine session.
+/// Returns the args of the session launched from the TUI it hands over to.
+fn run_idle_watcher(args: Args, config: &Config) -> Result<Option<Args>> {
     debug_log("Starting idle watcher mode");
 
     // Check if gamescope is already running (Sunshine may have started it)
     if is_gamescope_running() {
         debug_log("Gamescope already running, exiting idle watcher");
-        println!("Gamescope already running (started by Sunshine), exiting.");
-        return Ok(());
+        info!("Gamescope already running (started by Sunshine), exiting.");
+        return Ok(None);
     }
 
+    // Scan while the terminal is still ours, so finding no gamepads because
+    // /dev/input can't be read is shown rather than only logged
+    let gamepads = open_gamepads(true);
+
     // Set up terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    stdout.execute(EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+    let terminal_guard = TerminalGuard::enter()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
     // Set up input channel for controller
     let (tx, rx) = mpsc::channel::<InputEvent>();
-    spawn_idle_controller_reader(tx);
+    spawn_idle_controller_reader(tx, gamepads);
 
     let mut controller_connected = false;
     let mut should_start_tui = false;
@@ -1378,6 +3109,10 @@ fn run_idle_watcher(args: Args) -> Result<()> {
 
     // Main loop
     loop {
+        if signals::terminated() {
+            break;
+        }
+
         // Check if gamescope started (Sunshine launched it)
         if is_gamescope_running() {
             debug_log("Idle watcher: Gamescope detected, exiting");
@@ -1419,6 +3154,11 @@ fn run_idle_watcher(args: Args) -> Result<()> {
                             should_quit = true;
                             break;
                         }
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            debug_log("Idle watcher: Ctrl+C quit");
+                            should_quit = true;
+                            break;
+                        }
                         _ => {}
                     }
                 }
@@ -1427,20 +3167,131 @@ fn run_idle_watcher(args: Args) -> Result<()> {
     }
 
     // Restore terminal
-    disable_raw_mode()?;
-    terminal.backend_mut().execute(LeaveAlternateScreen)?;
+    drop(terminal_guard);
 
     if should_start_tui {
-        println!("\nStarting monitor selection...\n");
-        thread::sleep(Duration::from_millis(500));
-        return run_tui_launcher(args);
+        info!("\nStarting monitor selection...\n");
+        pause(Duration::from_millis(500));
+        return run_tui_launcher(args, config);
     }
 
     if should_quit {
-        println!("Exiting to shell.");
+        info!("Exiting to shell.");
     } else {
-        println!("Sunshine session detected, exiting idle watcher.");
+        info!("Sunshine session detected, exiting idle watcher.");
     }
 
-    Ok(())
+    Ok(None)
+}
+
+// ============================================================================
+// Daemon Mode Implementation
+// ============================================================================
+
+/// How often --daemon checks the connector's status
+const DAEMON_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long the connector has to stay connected before --daemon launches,
+/// so a TV still doing its HDMI handshake or a loose cable doesn't start
+/// (and kill) a session every few seconds
+const DAEMON_DEBOUNCE: Duration = Duration::from_secs(3);
+
+/// Whether the connector's sysfs status says connected
+fn connector_connected(connector_name: &str) -> bool {
+    fs::read_to_string(Path::new(DRM_PATH).join(connector_name).join("status"))
+        .is_ok_and(|status| status.trim() == "connected")
+}
+
+/// Watch the --display connector and run a session each time it becomes
+/// connected. After a session the connector has to be unplugged (or the
+/// TV turned off) before the next launch, so quitting doesn't bring the
+/// session straight back.
+fn run_daemon(args: &Args, config: &Config) -> Result<()> {
+    let Some(ref name) = args.display else {
+        anyhow::bail!("--daemon needs --display (or a --profile with a display) to know which connector to watch");
+    };
+    // A disconnected display has no EDID, so a monitor name only works
+    // while it's connected; the connector it's on is watched from then on
+    let connector = if Path::new(DRM_PATH).join(name).exists() {
+        name.clone()
+    } else {
+        find_display(&connected_displays(args)?, name)
+            .context("--daemon watches a connector; give its name, or connect the display before starting it")?
+            .connector_name
+            .clone()
+    };
+
+    start_status_server(args, &connector);
+
+    info!("Waiting for {} to be connected (Ctrl+C to exit)...", connector);
+
+    let session_args = Args { daemon: false, ..args.clone() };
+    let mut armed = true;
+    let mut connected_since: Option<Instant> = None;
+
+    loop {
+        if !connector_connected(&connector) {
+            if !armed || connected_since.is_some() {
+                debug_log(&format!("Daemon: {} disconnected", connector));
+            }
+            armed = true;
+            connected_since = None;
+        } else if armed {
+            let since = *connected_since.get_or_insert_with(|| {
+                debug_log(&format!("Daemon: {} connected, waiting for it to settle", connector));
+                Instant::now()
+            });
+
+            if since.elapsed() >= DAEMON_DEBOUNCE {
+                armed = false;
+                connected_since = None;
+
+                if is_gamescope_running() {
+                    info!("{} connected, but gamescope is already running; not launching", connector);
+                } else {
+                    info!("{} connected, launching", connector);
+                    // A failed launch shouldn't stop the daemon
+                    if let Err(e) = run_session(session_args.clone(), config) {
+                        eprintln!("⚠ {:#}", e);
+                    }
+                    info!("Session ended, waiting for {} to be reconnected...", connector);
+                }
+            }
+        }
+
+        thread::sleep(DAEMON_POLL_INTERVAL);
+    }
+}
+
+/// Serve --daemon's status endpoint; failing to listen only warns, since
+/// the daemon works without it
+#[cfg(feature = "status-server")]
+fn start_status_server(args: &Args, connector: &str) {
+    use console_mode::status::{spawn_server, Status, StatusDisplay, DEFAULT_LISTEN};
+
+    let addr = args.listen.unwrap_or_else(|| DEFAULT_LISTEN.parse().expect("DEFAULT_LISTEN is a valid address"));
+    let display_args = args.clone();
+    let connector = connector.to_string();
+    let status = move || Status {
+        connector: connector.clone(),
+        session_active: is_gamescope_running(),
+        displays: connected_displays(&display_args)
+            .inspect_err(|e| debug_log(&format!("Status endpoint: {:#}", e)))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|d| StatusDisplay { connector: d.connector_name, resolution: d.resolution, model: d.model })
+            .collect(),
+    };
+
+    match spawn_server(addr, status) {
+        Ok(addr) => info!("Serving status on http://{}/status", addr),
+        Err(e) => eprintln!("⚠ Status endpoint disabled: {:#}", e),
+    }
+}
+
+#[cfg(not(feature = "status-server"))]
+fn start_status_server(args: &Args, _connector: &str) {
+    if args.listen.is_some() {
+        println!("⚠ --listen needs console-mode built with the `status-server` feature");
+    }
 }
@@ -0,0 +1,332 @@
+//! Display capability detection: EDID (cached, through edid-decode or the
+//! built-in parser), DRM connector state and the user's overrides
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crate::cache;
+use crate::cli::{Args, ColorFormat};
+use crate::debug_log;
+use crate::display::{find_display, DisplayInfo};
+use crate::gamescope::whole_hz;
+use crate::drm::connector_hdr_state;
+use crate::error::{Error, Result};
+use crate::edid::{apply_refresh_fallback, default_capabilities, edid_decode_checksum_error, edid_max_refresh, parse_edid_bytes, parse_edid_capabilities, parse_edid_text, DisplayCapabilities};
+use crate::runner::CommandRunner;
+
+/// Detected capabilities and the warnings from detecting them
+pub type ProbeResult = Result<(DisplayCapabilities, Vec<String>)>;
+
+/// Detect capabilities and apply the user's overrides without printing
+/// anything, so the TUI can show the result. Returns the warnings to show.
+pub fn probe_capabilities(runner: &dyn CommandRunner, display: &DisplayInfo, args: &Args) -> ProbeResult {
+    let mut warnings = Vec::new();
+
+    if display.modes_unavailable {
+        warnings.push(format!(
+            "{} reports connected but lists no modes (check the cable or port), assuming {}",
+            display.connector_name, display.resolution
+        ));
+    }
+
+    if args.safe_mode {
+        warnings.push("Safe mode enabled - using conservative defaults".to_string());
+        return Ok((DisplayCapabilities {
+            vrr: false,
+            hdr: false,
+            max_refresh_rate: 60,
+            max_bpc: 8,
+            vrr_min: None,
+            vrr_max: None,
+            physical_size_mm: None,
+            modes: Vec::new(),
+            max_tmds_mhz: None,
+            max_frl_gbps: None,
+            ycbcr420_modes: Vec::new(),
+        }, warnings));
+    }
+
+    let mut caps = match args.edid_file.as_ref().filter(|_| edid_file_applies(display, args)) {
+        Some(path) => {
+            if !path.is_file() {
+                return Err(Error::EdidFileMissing(path.clone()));
+            }
+            debug_log(&format!("{}: reading EDID from {}", display.connector_name, path.display()));
+            edid_capabilities(runner, display, path, !args.no_cache, &mut warnings)?
+        }
+        None => edid_capabilities(runner, display, &display.connector_path.join("edid"), !args.no_cache, &mut warnings)?,
+    };
+
+    // Some panels only show HDR through KMS, e.g. when the desktop had it on
+    if !caps.hdr && connector_hdr_state(display).is_some_and(|state| state.indicates_hdr()) {
+        debug_log(&format!("{}: HDR active according to DRM, not EDID", display.connector_name));
+        caps.hdr = true;
+    }
+
+    // Apply user overrides
+    if args.force_vrr {
+        caps.vrr = true;
+    } else if args.no_vrr {
+        caps.vrr = false;
+    }
+
+    if args.force_hdr {
+        caps.hdr = true;
+    } else if args.no_hdr {
+        caps.hdr = false;
+    }
+
+    if let Some(rate) = args.refresh_rate {
+        if args.clamp_refresh && rate > caps.max_refresh_rate as f32 {
+            warnings.push(format!(
+                "Requested {}Hz exceeds the display's {}Hz maximum, clamping",
+                rate, caps.max_refresh_rate
+            ));
+        } else {
+            if rate.fract() != 0.0 {
+                warnings.push(format!("gamescope only takes whole Hz, passing {}Hz as {}Hz", rate, whole_hz(rate)));
+            }
+            caps.max_refresh_rate = whole_hz(rate);
+        }
+    }
+
+    if let Some(max) = args.max_bpc {
+        caps.max_bpc = caps.max_bpc.min(max);
+    }
+    limit_bpc_to_hdmi_link(display, &mut caps, args, &mut warnings);
+
+    Ok((caps, warnings))
+}
+
+/// Whether --edid-file is for this display: only the one chosen with
+/// --display (or picked from the menu) gets it, so listing several displays
+/// doesn't give them all the file's capabilities
+fn edid_file_applies(display: &DisplayInfo, args: &Args) -> bool {
+    let applies = args.display.as_ref()
+        .is_some_and(|name| find_display(std::slice::from_ref(display), name).is_ok());
+    if !applies {
+        debug_log(&format!("{}: not the --display display, ignoring --edid-file", display.connector_name));
+    }
+    applies
+}
+
+/// Lower `max_bpc` to what an HDMI link to the display carries at its
+/// resolution and refresh rate, in RGB or (unless --color-format rgb) 4:2:0.
+/// DisplayPort isn't checked: the EDID doesn't say what the link negotiates.
+fn limit_bpc_to_hdmi_link(display: &DisplayInfo, caps: &mut DisplayCapabilities, args: &Args, warnings: &mut Vec<String>) {
+    if !display.output_name().starts_with("HDMI") || caps.max_bpc <= 8 {
+        return;
+    }
+
+    let bpc = [12, 10].into_iter()
+        .filter(|&bpc| bpc <= caps.max_bpc)
+        .find(|&bpc| hdmi_pixel_format(display, caps, bpc, args.color_format).is_some())
+        .unwrap_or(8);
+
+    if bpc < caps.max_bpc {
+        warnings.push(format!(
+            "{} at {}Hz doesn't fit {}-bit color over HDMI, limiting to {}-bit",
+            display.resolution, caps.max_refresh_rate, caps.max_bpc, bpc
+        ));
+        caps.max_bpc = bpc;
+    }
+}
+
+/// How an HDMI link to the display carries its resolution at the detected
+/// refresh rate and `bpc` bits per color: in RGB (`Some(false)`) if it fits,
+/// else in YCbCr 4:2:0 (`Some(true)`), of those `color_format` allows. Auto
+/// only picks 4:2:0 if the EDID lists the mode for it. `None` when neither fits.
+pub fn hdmi_pixel_format(display: &DisplayInfo, caps: &DisplayCapabilities, bpc: u32, color_format: ColorFormat) -> Option<bool> {
+    let sink_takes_420 = caps.ycbcr420_modes.contains(&(display.width, display.height, caps.max_refresh_rate));
+    let formats: &[bool] = match color_format {
+        ColorFormat::Rgb => &[false],
+        ColorFormat::Ycbcr420 => &[true],
+        ColorFormat::Auto if sink_takes_420 => &[false, true],
+        ColorFormat::Auto => &[false],
+    };
+    formats.iter().copied().find(|&ycbcr420| {
+        caps.hdmi_carries(display.width, display.height, caps.max_refresh_rate, bpc, ycbcr420)
+    })
+}
+
+/// Capabilities from an EDID file (the connector's, or --edid-file), via the
+/// cache, edid-decode or the built-in parser, or defaults when there's no
+/// usable EDID
+fn edid_capabilities(runner: &dyn CommandRunner, display: &DisplayInfo, edid_file: &Path, use_cache: bool, warnings: &mut Vec<String>) -> Result<DisplayCapabilities> {
+    if !edid_file.exists() || !edid_file.is_file() {
+        warnings.push("EDID file not accessible, using defaults".to_string());
+        return Ok(default_capabilities(display));
+    }
+
+    // Read EDID binary data
+    let edid_data = fs::read(edid_file)
+        .map_err(|source| Error::EdidRead { path: edid_file.to_path_buf(), source })?;
+
+    if edid_data.is_empty() {
+        warnings.push("EDID file is empty, using defaults".to_string());
+        return Ok(default_capabilities(display));
+    }
+
+    // A different monitor has different EDID bytes, so never a stale hit.
+    // Only what the EDID says is cached; the fallback for a missing refresh
+    // rate depends on the resolution, which --resolution can change.
+    if use_cache {
+        if let Some(mut caps) = cache::load_capabilities(&edid_data) {
+            debug_log(&format!("{}: using cached capabilities", display.connector_name));
+            apply_refresh_fallback(&mut caps, display);
+            return Ok(caps);
+        }
+    }
+
+    // Use edid-decode to parse EDID
+    let edid_decode_output = runner.output(&mut Command::new("edid-decode"), &edid_data);
+
+    // Detailed timing descriptors in the raw EDID carry exact refresh rates
+    let dtd_refresh = edid_max_refresh(&edid_data).filter(|&rate| rate <= 500);
+
+    // Only trust edid-decode's output if it succeeded on a valid EDID;
+    // a partial parse of a broken one is worse than the built-in parser
+    let edid_text = match edid_decode_output {
+        Ok(output) => {
+            let edid_text = String::from_utf8_lossy(&output.stdout).into_owned();
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !output.status.success() {
+                debug_log(&format!("edid-decode {} for {}: {}", output.status, display.connector_name, stderr.trim()));
+                warnings.push(format!("edid-decode failed ({})", output.status));
+                None
+            } else if edid_decode_checksum_error(&edid_text) {
+                debug_log(&format!("edid-decode reported EDID checksum errors for {}", display.connector_name));
+                warnings.push("edid-decode found EDID checksum errors".to_string());
+                None
+            } else {
+                if !stderr.trim().is_empty() {
+                    debug_log(&format!("edid-decode stderr for {}: {}", display.connector_name, stderr.trim()));
+                }
+                Some(edid_text)
+            }
+        }
+        Err(e) => {
+            debug_log(&format!("Could not run edid-decode: {}", e));
+            warnings.push("Could not run edid-decode".to_string());
+            None
+        }
+    };
+
+    let capabilities = if let Some(edid_text) = edid_text {
+        let mut caps = parse_edid_text(&edid_text);
+        if let Some(rate) = dtd_refresh {
+            caps.max_refresh_rate = caps.max_refresh_rate.max(rate);
+        }
+        // Only edid-decode is slow enough to be worth caching
+        if let Err(e) = cache::save_capabilities(&edid_data, &caps) {
+            debug_log(&format!("Failed to cache capabilities: {}", e));
+        }
+        apply_refresh_fallback(&mut caps, display);
+        caps
+    } else if let Some(caps) = parse_edid_bytes(&edid_data, display) {
+        warnings.push("Using built-in EDID parser".to_string());
+        caps
+    } else {
+        warnings.push("EDID is invalid, using defaults".to_string());
+        default_capabilities(display)
+    };
+
+    Ok(capabilities)
+}
+
+/// Copy a display's raw EDID to `path` (--dump-edid) and, if edid-decode
+/// runs, its text to `<path>.txt`. Returns the text file if one was written.
+pub fn dump_edid(runner: &dyn CommandRunner, display: &DisplayInfo, path: &Path) -> Result<Option<PathBuf>> {
+    let edid_file = display.connector_path.join("edid");
+    let edid_data = fs::read(&edid_file)
+        .map_err(|source| Error::EdidRead { path: edid_file.clone(), source })?;
+    if edid_data.is_empty() {
+        return Err(Error::NoEdid(display.connector_name.clone()));
+    }
+    fs::write(path, &edid_data)
+        .map_err(|source| Error::EdidDump { path: path.to_path_buf(), source })?;
+
+    // edid-decode fails on a broken EDID, but what it printed up to the
+    // error is still worth attaching
+    let output = match runner.output(&mut Command::new("edid-decode"), &edid_data) {
+        Ok(output) if !output.stdout.is_empty() => output,
+        Ok(output) => {
+            debug_log(&format!("edid-decode {} printed nothing for {}", output.status, display.connector_name));
+            return Ok(None);
+        }
+        Err(e) => {
+            debug_log(&format!("Could not run edid-decode: {}", e));
+            return Ok(None);
+        }
+    };
+    let mut text_path = path.as_os_str().to_owned();
+    text_path.push(".txt");
+    let text_path = PathBuf::from(text_path);
+    fs::write(&text_path, &output.stdout)
+        .map_err(|source| Error::EdidDump { path: text_path.clone(), source })?;
+    Ok(Some(text_path))
+}
+
+/// How long each EDID detection step takes for one display
+/// (--benchmark-detection)
+#[derive(Debug)]
+pub struct DetectionTimings {
+    pub edid_bytes: usize,
+    pub read_edid: Duration,
+    pub cache_lookup: Duration,
+    /// Whether the cache has capabilities for this EDID
+    pub cache_hit: bool,
+    /// Spawning edid-decode and reading its output; `None` if it couldn't run
+    pub edid_decode: Option<Duration>,
+    /// `parse_edid_capabilities` on edid-decode's output
+    pub parse_edid_decode: Option<Duration>,
+    /// The built-in parser on the raw bytes
+    pub parse_builtin: Duration,
+}
+
+/// Time every EDID detection step for a display, without the cache
+/// short-circuiting the rest and without writing to it
+pub fn time_detection(runner: &dyn CommandRunner, display: &DisplayInfo) -> Result<DetectionTimings> {
+    let edid_file = display.connector_path.join("edid");
+
+    let started = Instant::now();
+    let edid_data = fs::read(&edid_file)
+        .map_err(|source| Error::EdidRead { path: edid_file.clone(), source })?;
+    let read_edid = started.elapsed();
+    if edid_data.is_empty() {
+        return Err(Error::NoEdid(display.connector_name.clone()));
+    }
+
+    let started = Instant::now();
+    let cache_hit = cache::load_capabilities(&edid_data).is_some();
+    let cache_lookup = started.elapsed();
+
+    let started = Instant::now();
+    let output = runner.output(&mut Command::new("edid-decode"), &edid_data)
+        .inspect_err(|e| debug_log(&format!("Could not run edid-decode: {}", e)))
+        .ok();
+    let edid_decode = output.is_some().then(|| started.elapsed());
+
+    let parse_edid_decode = output.filter(|output| output.status.success()).map(|output| {
+        let edid_text = String::from_utf8_lossy(&output.stdout);
+        let started = Instant::now();
+        parse_edid_capabilities(&edid_text, display);
+        started.elapsed()
+    });
+
+    let started = Instant::now();
+    parse_edid_bytes(&edid_data, display);
+    let parse_builtin = started.elapsed();
+
+    Ok(DetectionTimings {
+        edid_bytes: edid_data.len(),
+        read_edid,
+        cache_lookup,
+        cache_hit,
+        edid_decode,
+        parse_edid_decode,
+        parse_builtin,
+    })
+}
@@ -0,0 +1,166 @@
+//! Running external programs (gamescope, Steam, edid-decode, launchers)
+//! behind a trait, so tests can check what would be run without running it
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::signals;
+
+pub trait CommandRunner: Sync {
+    /// Run a command with inherited stdio and wait for it (for gamescope)
+    fn status(&self, cmd: &mut Command) -> io::Result<ExitStatus>;
+
+    /// Like `status`, but with stderr piped to `on_line` a line at a time
+    /// (for gamescope --capture-gamescope)
+    fn status_with_stderr(&self, cmd: &mut Command, on_line: &mut dyn FnMut(&str)) -> io::Result<ExitStatus>;
+
+    /// Run a command with `input` on its stdin, capturing stdout and stderr
+    fn output(&self, cmd: &mut Command, input: &[u8]) -> io::Result<Output>;
+}
+
+/// Runs commands for real through `std::process::Command`, with
+/// `status` in a process group that signals are forwarded to
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn status(&self, cmd: &mut Command) -> io::Result<ExitStatus> {
+        signals::run_child(cmd)
+    }
+
+    fn status_with_stderr(&self, cmd: &mut Command, on_line: &mut dyn FnMut(&str)) -> io::Result<ExitStatus> {
+        signals::run_child_with(cmd.stderr(Stdio::piped()), |child| {
+            let (tx, rx) = mpsc::channel();
+            if let Some(stderr) = child.stderr.take() {
+                thread::spawn(move || {
+                    for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                        if tx.send(line).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+
+            // Programs gamescope started may keep the pipe open after it
+            // exits, so stop at its exit rather than at the end of the pipe
+            loop {
+                match rx.recv_timeout(Duration::from_millis(100)) {
+                    Ok(line) => on_line(&line),
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        if let Some(status) = child.try_wait()? {
+                            // Its last lines may still be on their way
+                            let deadline = Instant::now() + Duration::from_secs(1);
+                            while let Ok(line) = rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                                on_line(&line);
+                            }
+                            return Ok(status);
+                        }
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return child.wait(),
+                }
+            }
+        })
+    }
+
+    fn output(&self, cmd: &mut Command, input: &[u8]) -> io::Result<Output> {
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input)?;
+        }
+        child.wait_with_output()
+    }
+}
+
+/// Records every command instead of running it, and answers with canned
+/// output per program (success with no output if none was given)
+#[derive(Default)]
+pub struct MockRunner {
+    outputs: HashMap<String, Output>,
+    calls: Mutex<Vec<Vec<String>>>,
+}
+
+impl MockRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer runs of `program` with this exit code and stdout
+    pub fn with_output(mut self, program: &str, code: i32, stdout: &str) -> Self {
+        self.outputs.insert(program.to_string(), Output {
+            status: exit_status(code),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: Vec::new(),
+        });
+        self
+    }
+
+    /// The argv of every command run so far, in order
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn record(&self, cmd: &Command) -> Output {
+        let argv = command_argv(cmd);
+        let output = self.outputs.get(&argv[0]).cloned().unwrap_or(Output {
+            status: exit_status(0),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        });
+        self.calls.lock().unwrap().push(argv);
+        output
+    }
+}
+
+impl CommandRunner for MockRunner {
+    fn status(&self, cmd: &mut Command) -> io::Result<ExitStatus> {
+        Ok(self.record(cmd).status)
+    }
+
+    fn status_with_stderr(&self, cmd: &mut Command, on_line: &mut dyn FnMut(&str)) -> io::Result<ExitStatus> {
+        let output = self.record(cmd);
+        String::from_utf8_lossy(&output.stderr).lines().for_each(on_line);
+        Ok(output.status)
+    }
+
+    fn output(&self, cmd: &mut Command, _input: &[u8]) -> io::Result<Output> {
+        Ok(self.record(cmd))
+    }
+}
+
+/// Program and arguments of a command
+pub fn command_argv(cmd: &Command) -> Vec<String> {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect()
+}
+
+fn exit_status(code: i32) -> ExitStatus {
+    // A wait status, with the exit code in the second byte
+    ExitStatus::from_raw(code << 8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stderr_lines_reach_the_callback() {
+        let mut lines = Vec::new();
+        let status = SystemRunner.status_with_stderr(
+            Command::new("sh").args(["-c", "echo one >&2; echo two >&2; exit 3"]),
+            &mut |line| lines.push(line.to_string()),
+        ).unwrap();
+
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(lines, ["one", "two"]);
+    }
+}
@@ -0,0 +1,116 @@
+//! Display manager session files written by --install-session
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// Name of the wrapper script and the session entry
+const SESSION_NAME: &str = "console-mode-session";
+
+/// Files an install writes, with their contents
+pub struct SessionFiles {
+    pub wrapper_path: PathBuf,
+    pub wrapper: String,
+    pub desktop_path: PathBuf,
+    pub desktop: String,
+}
+
+impl SessionFiles {
+    /// `<prefix>/bin/console-mode-session`, running `binary` with `args`, and
+    /// `<prefix>/share/wayland-sessions/console-mode-session.desktop` pointing at it
+    pub fn new(prefix: &Path, binary: &Path, args: &[String]) -> Self {
+        let wrapper_path = prefix.join("bin").join(SESSION_NAME);
+        let desktop_path = prefix.join("share/wayland-sessions").join(format!("{}.desktop", SESSION_NAME));
+
+        let command: Vec<String> = std::iter::once(binary.to_string_lossy().into_owned())
+            .chain(args.iter().cloned())
+            .map(|arg| shell_quote(&arg))
+            .collect();
+        let wrapper = format!(
+            "#!/bin/sh\n# Generated by console-mode --install-session\nexec {} \"$@\"\n",
+            command.join(" ")
+        );
+
+        let desktop = format!(
+            "[Desktop Entry]\n\
+             Name=Console Mode\n\
+             Comment=Steam Big Picture in gamescope\n\
+             Exec={}\n\
+             Type=Application\n\
+             DesktopNames=gamescope\n",
+            wrapper_path.display()
+        );
+
+        SessionFiles { wrapper_path, wrapper, desktop_path, desktop }
+    }
+
+    /// Write both files, making the wrapper executable
+    pub fn install(&self) -> Result<()> {
+        for (path, contents) in [(&self.wrapper_path, &self.wrapper), (&self.desktop_path, &self.desktop)] {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .context(format!("Failed to create {}", dir.display()))?;
+            }
+            fs::write(path, contents)
+                .context(format!("Failed to write {} (installing to this prefix may need root)", path.display()))?;
+        }
+
+        fs::set_permissions(&self.wrapper_path, fs::Permissions::from_mode(0o755))
+            .context(format!("Failed to make {} executable", self.wrapper_path.display()))
+    }
+}
+
+/// The command line args to bake into the session, i.e. everything but
+/// the install flags themselves
+pub fn session_args(argv: &[String]) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut iter = argv.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--install-session" | "--dry-run" => {}
+            "--prefix" => {
+                iter.next();
+            }
+            _ if arg.starts_with("--prefix=") => {}
+            _ => args.push(arg.clone()),
+        }
+    }
+    args
+}
+
+/// Quote an argument for a POSIX shell, leaving plain ones as they are
+pub fn shell_quote(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg.chars().all(|c| c.is_ascii_alphanumeric() || "_-./=:,+@%".contains(c));
+
+    if is_plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn install_flags_are_not_baked_in() {
+        let argv = strings(&["--install-session", "--prefix", "/usr", "--tui-launcher", "--prefix=/opt", "--env", "A=b c"]);
+        assert_eq!(session_args(&argv), strings(&["--tui-launcher", "--env", "A=b c"]));
+    }
+
+    #[test]
+    fn wrapper_runs_binary_with_quoted_args() {
+        let files = SessionFiles::new(Path::new("/usr/local"), Path::new("/usr/bin/console-mode"), &strings(&["--env", "A=b c"]));
+        assert_eq!(files.wrapper_path, Path::new("/usr/local/bin/console-mode-session"));
+        assert!(files.wrapper.ends_with("exec /usr/bin/console-mode --env 'A=b c' \"$@\"\n"));
+        assert!(files.desktop.contains("Exec=/usr/local/bin/console-mode-session\n"));
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+}
@@ -0,0 +1,114 @@
+//! SIGTERM/SIGINT/SIGHUP handling, so stopping console-mode (e.g. systemd
+//! stopping the session) doesn't leave gamescope running and holding DRM master
+//!
+//! gamescope runs in its own process group, together with Steam and the
+//! games it starts. A signal that arrives while it runs is forwarded to the
+//! whole group, and we exit with it once gamescope is gone, or once the
+//! caller is done cleaning up if it deferred that. In a TUI the signal is
+//! only recorded, so the TUI can quit and restore the terminal first.
+//! Anywhere else the signal does what it always does.
+
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, ExitStatus};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+use crate::debug_log;
+
+const SIGNALS: [libc::c_int; 3] = [libc::SIGTERM, libc::SIGINT, libc::SIGHUP];
+
+/// Process group of the running child, 0 if none
+static CHILD_PGID: AtomicI32 = AtomicI32::new(0);
+/// A TUI owns the terminal and checks `terminated()` in its loop
+static TUI_ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Signal received while a child or TUI was running, 0 if none
+static PENDING: AtomicI32 = AtomicI32::new(0);
+/// A caller has cleanup to do before we exit on a signal
+static DEFER_EXIT: AtomicBool = AtomicBool::new(false);
+
+/// Install the handler for SIGTERM, SIGINT and SIGHUP
+pub fn install() {
+    for sig in SIGNALS {
+        // SAFETY: the handler only does async-signal-safe things
+        unsafe {
+            libc::signal(sig, handle_signal as extern "C" fn(libc::c_int) as libc::sighandler_t);
+        }
+    }
+}
+
+extern "C" fn handle_signal(sig: libc::c_int) {
+    let pgid = CHILD_PGID.load(Ordering::SeqCst);
+    if pgid > 0 {
+        PENDING.store(sig, Ordering::SeqCst);
+        // SAFETY: kill is async-signal-safe
+        unsafe {
+            libc::kill(-pgid, sig);
+        }
+    } else if TUI_ACTIVE.load(Ordering::SeqCst) {
+        PENDING.store(sig, Ordering::SeqCst);
+    } else {
+        reraise(sig);
+    }
+}
+
+/// Die of `sig` as if it had never been handled
+fn reraise(sig: libc::c_int) {
+    // SAFETY: signal and raise are async-signal-safe
+    unsafe {
+        libc::signal(sig, libc::SIG_DFL);
+        libc::raise(sig);
+    }
+    // Only reached if the default action doesn't end the process
+    std::process::exit(128 + sig);
+}
+
+/// Whether a signal asked us to stop
+pub fn terminated() -> bool {
+    PENDING.load(Ordering::SeqCst) != 0
+}
+
+/// Exit with the signal that asked us to stop, if one did
+pub fn exit_if_terminated() {
+    let sig = PENDING.load(Ordering::SeqCst);
+    if sig != 0 {
+        debug_log(&format!("Exiting on signal {}", sig));
+        reraise(sig);
+    }
+}
+
+/// Have [`run_child`] return an error rather than exit when a signal arrived
+/// while the child ran, so the caller can clean up and then
+/// [`exit_if_terminated`]
+pub fn defer_exit(defer: bool) {
+    DEFER_EXIT.store(defer, Ordering::SeqCst);
+}
+
+/// Mark a TUI as running, so signals are left for it to handle
+pub fn set_tui_active(active: bool) {
+    TUI_ACTIVE.store(active, Ordering::SeqCst);
+}
+
+/// Run a command in its own process group and wait for it, forwarding
+/// signals to the group (Ctrl+C included, since we keep the terminal).
+/// Exits once the child is gone if a signal arrived meanwhile, or fails
+/// if that's been deferred with [`defer_exit`].
+pub fn run_child(cmd: &mut Command) -> io::Result<ExitStatus> {
+    run_child_with(cmd, |child| child.wait())
+}
+
+/// [`run_child`], with `wait` doing the waiting, e.g. while reading the
+/// child's piped output
+pub fn run_child_with(cmd: &mut Command, wait: impl FnOnce(&mut Child) -> io::Result<ExitStatus>) -> io::Result<ExitStatus> {
+    let mut child = cmd.process_group(0).spawn()?;
+    CHILD_PGID.store(child.id() as i32, Ordering::SeqCst);
+
+    let status = wait(&mut child);
+    CHILD_PGID.store(0, Ordering::SeqCst);
+
+    let sig = PENDING.load(Ordering::SeqCst);
+    if sig != 0 && DEFER_EXIT.load(Ordering::SeqCst) {
+        return Err(io::Error::other(format!("stopped by signal {}", sig)));
+    }
+    exit_if_terminated();
+    status
+}
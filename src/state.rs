@@ -0,0 +1,36 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+/// Read the connector name of the display picked last time, if any
+pub fn load_last_display() -> Option<String> {
+    let contents = fs::read_to_string(last_display_path()?).ok()?;
+    let name = contents.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Remember the picked display for the next launch
+pub fn save_last_display(connector_name: &str) -> Result<()> {
+    let path = last_display_path().context("Could not determine state directory")?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .context(format!("Failed to create state directory {}", dir.display()))?;
+    }
+
+    fs::write(&path, format!("{}\n", connector_name))
+        .context(format!("Failed to write {}", path.display()))
+}
+
+/// `$XDG_STATE_HOME/console-mode`, defaulting to `~/.local/state/console-mode`
+pub fn state_dir() -> Option<PathBuf> {
+    let state_home = match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".local/state"),
+    };
+    Some(state_home.join("console-mode"))
+}
+
+fn last_display_path() -> Option<PathBuf> {
+    Some(state_dir()?.join("last-display"))
+}
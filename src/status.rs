@@ -0,0 +1,132 @@
+//! A tiny HTTP endpoint for --daemon (`status-server` feature), so a
+//! dashboard can ask which displays are connected and whether a session
+//! is running: `GET /status` answers with JSON
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use crate::debug_log;
+
+/// Where the endpoint listens without --listen; localhost only, since
+/// there's no authentication
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:7878";
+
+/// What `GET /status` returns
+#[derive(Debug, Serialize)]
+pub struct Status {
+    /// The connector --daemon watches
+    pub connector: String,
+    /// Whether gamescope is running
+    pub session_active: bool,
+    pub displays: Vec<StatusDisplay>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusDisplay {
+    pub connector: String,
+    pub resolution: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// Listen on `addr` and answer requests from a background thread, calling
+/// `status` for each one. Returns the bound address (for port 0).
+pub fn spawn_server<F>(addr: SocketAddr, status: F) -> Result<SocketAddr>
+where
+    F: Fn() -> Status + Send + 'static,
+{
+    let listener = TcpListener::bind(addr)
+        .context(format!("Failed to listen on {}", addr))?;
+    let local_addr = listener.local_addr()
+        .context("Failed to get the status endpoint's address")?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream
+                .context("Failed to accept a status connection")
+                .and_then(|stream| handle_connection(stream, &status));
+            if let Err(e) = result {
+                debug_log(&format!("Status endpoint: {:#}", e));
+            }
+        }
+    });
+
+    Ok(local_addr)
+}
+
+fn handle_connection(mut stream: TcpStream, status: &dyn Fn() -> Status) -> Result<()> {
+    // A client that never finishes its request shouldn't block the others
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Read the headers too, so closing doesn't reset the connection before
+    // the client has the response
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+
+    let (status_line, body) = respond(&request_line, status)?;
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status_line, body.len(), body
+    );
+    stream.write_all(response.as_bytes())?;
+    Ok(())
+}
+
+/// The status line and JSON body for a request line like `GET /status HTTP/1.1`
+fn respond(request_line: &str, status: &dyn Fn() -> Status) -> Result<(&'static str, String)> {
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+
+    Ok(match (method, path) {
+        ("GET", "/status" | "/") => {
+            ("200 OK", serde_json::to_string(&status()).context("Failed to serialize status")?)
+        }
+        ("GET", _) => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+        _ => ("405 Method Not Allowed", r#"{"error":"method not allowed"}"#.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn serves_status_as_json() {
+        let addr = spawn_server("127.0.0.1:0".parse().unwrap(), || Status {
+            connector: "card1-HDMI-A-1".to_string(),
+            session_active: true,
+            displays: vec![StatusDisplay {
+                connector: "card1-HDMI-A-1".to_string(),
+                resolution: "3840x2160".to_string(),
+                model: None,
+            }],
+        })
+        .unwrap();
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"GET /status HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains(r#""session_active":true"#), "{}", response);
+        assert!(response.contains(r#""resolution":"3840x2160""#), "{}", response);
+    }
+
+    #[test]
+    fn other_paths_and_methods_are_rejected() {
+        let status = || Status { connector: String::new(), session_active: false, displays: Vec::new() };
+        assert_eq!(respond("GET /nope HTTP/1.1", &status).unwrap().0, "404 Not Found");
+        assert_eq!(respond("POST /status HTTP/1.1", &status).unwrap().0, "405 Method Not Allowed");
+    }
+}
@@ -0,0 +1,170 @@
+//! TUI colors: built-in themes and per-color overrides
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Built-in TUI color themes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// Cyan borders and highlight, for dark terminals
+    #[default]
+    Dark,
+    /// Blue borders and highlight, for light terminals
+    Light,
+    /// White and bright colors only, nothing dim
+    HighContrast,
+}
+
+/// Colors the TUIs draw with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub border: Color,
+    /// Selected list entry
+    pub highlight_fg: Color,
+    pub highlight_bg: Color,
+    /// Help keys that move around or filter
+    pub navigate_key: Color,
+    /// Help keys that select or confirm
+    pub select_key: Color,
+    /// Help keys that go back or quit
+    pub back_key: Color,
+    /// Hints and countdowns
+    pub hint: Color,
+    /// Something on or working: a supported capability, a connected controller
+    pub good: Color,
+    /// Something off or failed
+    pub bad: Color,
+    /// Detection warnings and waiting states
+    pub warning: Color,
+    /// The HDR tag in the display list
+    pub hdr_badge: Color,
+    /// The refresh rate tag in the display list
+    pub refresh_badge: Color,
+}
+
+impl Theme {
+    pub fn builtin(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Theme {
+                border: Color::Cyan,
+                highlight_fg: Color::Black,
+                highlight_bg: Color::Cyan,
+                navigate_key: Color::Yellow,
+                select_key: Color::Green,
+                back_key: Color::Red,
+                hint: Color::DarkGray,
+                good: Color::Green,
+                bad: Color::Red,
+                warning: Color::Yellow,
+                hdr_badge: Color::Magenta,
+                refresh_badge: Color::Blue,
+            },
+            ThemeName::Light => Theme {
+                border: Color::Blue,
+                highlight_fg: Color::White,
+                highlight_bg: Color::Blue,
+                navigate_key: Color::Magenta,
+                select_key: Color::Green,
+                back_key: Color::Red,
+                hint: Color::Gray,
+                good: Color::Green,
+                bad: Color::Red,
+                // Yellow doesn't show up on a light background
+                warning: Color::Rgb(0xaf, 0x5f, 0x00),
+                hdr_badge: Color::Magenta,
+                refresh_badge: Color::Blue,
+            },
+            ThemeName::HighContrast => Theme {
+                border: Color::White,
+                highlight_fg: Color::Black,
+                highlight_bg: Color::LightYellow,
+                navigate_key: Color::LightYellow,
+                select_key: Color::LightCyan,
+                back_key: Color::LightMagenta,
+                hint: Color::White,
+                good: Color::LightCyan,
+                bad: Color::LightMagenta,
+                warning: Color::LightYellow,
+                hdr_badge: Color::White,
+                refresh_badge: Color::White,
+            },
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::builtin(ThemeName::Dark)
+    }
+}
+
+/// Colors that replace the theme's, as names ("cyan", "light-yellow"),
+/// "#rrggbb" or a 0-255 palette index
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct ThemeColors {
+    pub border: Option<String>,
+    pub highlight_fg: Option<String>,
+    pub highlight_bg: Option<String>,
+    pub navigate_key: Option<String>,
+    pub select_key: Option<String>,
+    pub back_key: Option<String>,
+    pub hint: Option<String>,
+    pub good: Option<String>,
+    pub bad: Option<String>,
+    pub warning: Option<String>,
+    pub hdr_badge: Option<String>,
+    pub refresh_badge: Option<String>,
+}
+
+/// The built-in theme with any overridden colors replaced
+pub fn build_theme(name: ThemeName, colors: &ThemeColors) -> Result<Theme> {
+    let mut theme = Theme::builtin(name);
+    let slots = [
+        ("border", &colors.border, &mut theme.border),
+        ("highlight_fg", &colors.highlight_fg, &mut theme.highlight_fg),
+        ("highlight_bg", &colors.highlight_bg, &mut theme.highlight_bg),
+        ("navigate_key", &colors.navigate_key, &mut theme.navigate_key),
+        ("select_key", &colors.select_key, &mut theme.select_key),
+        ("back_key", &colors.back_key, &mut theme.back_key),
+        ("hint", &colors.hint, &mut theme.hint),
+        ("good", &colors.good, &mut theme.good),
+        ("bad", &colors.bad, &mut theme.bad),
+        ("warning", &colors.warning, &mut theme.warning),
+        ("hdr_badge", &colors.hdr_badge, &mut theme.hdr_badge),
+        ("refresh_badge", &colors.refresh_badge, &mut theme.refresh_badge),
+    ];
+    for (field, value, slot) in slots {
+        if let Some(value) = value {
+            *slot = Color::from_str(value)
+                .ok()
+                .context(format!("Invalid color '{}' for {} (expected a name like \"cyan\", \"#rrggbb\" or 0-255)", value, field))?;
+        }
+    }
+    Ok(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_replace_only_the_given_colors() {
+        let colors = ThemeColors {
+            border: Some("magenta".to_string()),
+            highlight_bg: Some("#102030".to_string()),
+            ..Default::default()
+        };
+        let theme = build_theme(ThemeName::Dark, &colors).unwrap();
+        assert_eq!(theme.border, Color::Magenta);
+        assert_eq!(theme.highlight_bg, Color::Rgb(0x10, 0x20, 0x30));
+        assert_eq!(theme.highlight_fg, Theme::builtin(ThemeName::Dark).highlight_fg);
+
+        let bad = ThemeColors { hint: Some("not-a-color".to_string()), ..Default::default() };
+        assert!(build_theme(ThemeName::Light, &bad).is_err());
+    }
+}
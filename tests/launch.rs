@@ -0,0 +1,371 @@
+//! Launch behavior checked through `MockRunner`, without running gamescope,
+//! Steam or edid-decode
+
+use clap::Parser;
+use console_mode::cli::{Args, ColorFormat};
+use console_mode::display::DisplayInfo;
+use console_mode::edid::DisplayCapabilities;
+use console_mode::gamescope::{append_session_command, build_nested_args, build_virtual_output_args, gamescope_command, output_bpc};
+use console_mode::probe::{dump_edid, hdmi_pixel_format, probe_capabilities, time_detection};
+use console_mode::runner::{command_argv, CommandRunner, MockRunner};
+use console_mode::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn args(cli: &[&str]) -> Args {
+    Args::parse_from(std::iter::once("console-mode").chain(cli.iter().copied()))
+}
+
+fn display(connector_path: &Path) -> DisplayInfo {
+    DisplayInfo {
+        connector_name: "card1-HDMI-A-1".to_string(),
+        connector_path: connector_path.to_path_buf(),
+        resolution: "3840x2160".to_string(),
+        width: 3840,
+        height: 2160,
+        available_modes: vec![(3840, 2160), (1920, 1080)],
+        output_ambiguous: false,
+        gpu_id: None,
+        modes_unavailable: false,
+        model: None,
+        serial: None,
+        native_mode: Some((3840, 2160)),
+    }
+}
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+/// Stand-in for a connector's sysfs directory, holding just its EDID
+fn fake_connector(test_name: &str, edid: &[u8]) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("console-mode-launch-{}-{}", test_name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("edid"), edid).unwrap();
+    dir
+}
+
+#[test]
+fn gamescope_argv_for_vrr_hdr_display() {
+    let caps = DisplayCapabilities {
+        vrr: true,
+        hdr: true,
+        max_refresh_rate: 120,
+        max_bpc: 10,
+        vrr_min: Some(48),
+        vrr_max: Some(120),
+        physical_size_mm: None,
+        modes: Vec::new(),
+        max_tmds_mhz: None,
+        max_frl_gbps: None,
+        ycbcr420_modes: Vec::new(),
+    };
+    let args = args(&["--steam-args=-gamepadui", "--", "--force-grab-cursor"]);
+
+    let runner = MockRunner::new();
+    runner.status(&mut gamescope_command(&display(Path::new("/nonexistent")), &caps, &args)).unwrap();
+
+    assert_eq!(runner.calls(), [argv(&[
+        "gamescope",
+        "-W", "3840", "-H", "2160", "-r", "120",
+        "--prefer-output", "HDMI-A-1",
+        "--adaptive-sync", "--custom-refresh-rates", "48-120",
+        "--hdr-enabled", "--hdr-itm-enable",
+        "--mangoapp", "-f", "-e",
+        "--force-grab-cursor",
+        "--", "steam", "-bigpicture", "-gamepadui",
+    ])]);
+}
+
+#[test]
+fn gamescope_runs_command_instead_of_steam() {
+    let caps = DisplayCapabilities { max_refresh_rate: 60, max_bpc: 8, ..Default::default() };
+    let args = args(&["--windowed", "--render-resolution", "1920x1080", "--command", "kodi --standalone"]);
+
+    let runner = MockRunner::new();
+    runner.status(&mut gamescope_command(&display(Path::new("/nonexistent")), &caps, &args)).unwrap();
+
+    assert_eq!(runner.calls(), [argv(&[
+        "gamescope",
+        "-W", "3840", "-H", "2160", "-r", "60",
+        "-w", "1920", "-h", "1080",
+        "--prefer-output", "HDMI-A-1",
+        "--mangoapp", "-e",
+        "--", "kodi", "--standalone",
+    ])]);
+}
+
+#[test]
+fn gamescope_launches_steam_game_by_appid() {
+    let caps = DisplayCapabilities { max_refresh_rate: 60, max_bpc: 8, ..Default::default() };
+    let args = args(&["--steam-appid", "620", "--steam-args=-gamepadui"]);
+
+    let command = command_argv(&gamescope_command(&display(Path::new("/nonexistent")), &caps, &args));
+    assert!(command.ends_with(&argv(&["--", "steam", "-applaunch", "620", "-gamepadui"])), "{:?}", command);
+    assert!(Args::try_parse_from(["console-mode", "--steam-appid", "portal2"]).is_err());
+}
+
+#[test]
+fn bigpicture_flag_replaces_the_default() {
+    let caps = DisplayCapabilities { max_refresh_rate: 60, max_bpc: 8, ..Default::default() };
+    let gamepadui = args(&["--bigpicture-flag", "-gamepadui"]);
+    let no_flag = args(&["--bigpicture-flag="]);
+
+    let command = command_argv(&gamescope_command(&display(Path::new("/nonexistent")), &caps, &gamepadui));
+    assert!(command.ends_with(&argv(&["--", "steam", "-gamepadui"])), "{:?}", command);
+
+    // Nested, headless and fallback launches share the session command
+    let mut nested = std::process::Command::new("gamescope");
+    append_session_command(&mut nested, &gamepadui);
+    assert_eq!(command_argv(&nested), argv(&["gamescope", "--", "steam", "-gamepadui"]));
+
+    let mut bare = std::process::Command::new("gamescope");
+    append_session_command(&mut bare, &no_flag);
+    assert_eq!(command_argv(&bare), argv(&["gamescope", "--", "steam"]));
+}
+
+#[test]
+fn test_launch_runs_true_instead_of_steam() {
+    let caps = DisplayCapabilities { max_refresh_rate: 60, max_bpc: 8, ..Default::default() };
+    let command = command_argv(&gamescope_command(&display(Path::new("/nonexistent")), &caps, &args(&["--test-launch"])));
+    assert!(command.ends_with(&argv(&["--", "true"])), "{:?}", command);
+}
+
+#[test]
+fn rotate_forces_gamescope_orientation() {
+    let caps = DisplayCapabilities { max_refresh_rate: 60, max_bpc: 8, ..Default::default() };
+
+    let rotated = command_argv(&gamescope_command(&display(Path::new("/nonexistent")), &caps, &args(&["--rotate", "90"])));
+    assert!(rotated.windows(2).any(|pair| pair == ["--force-orientation", "left"]), "{:?}", rotated);
+
+    let unrotated = command_argv(&gamescope_command(&display(Path::new("/nonexistent")), &caps, &args(&[])));
+    assert!(!unrotated.iter().any(|arg| arg == "--force-orientation"), "{:?}", unrotated);
+    assert!(Args::try_parse_from(["console-mode", "--rotate", "45"]).is_err());
+}
+
+#[test]
+fn gamescope_runs_nothing_with_no_steam() {
+    let caps = DisplayCapabilities { max_refresh_rate: 60, max_bpc: 8, ..Default::default() };
+    let args = args(&["--no-steam"]);
+
+    let runner = MockRunner::new();
+    runner.status(&mut gamescope_command(&display(Path::new("/nonexistent")), &caps, &args)).unwrap();
+
+    assert_eq!(runner.calls(), [argv(&[
+        "gamescope",
+        "-W", "3840", "-H", "2160", "-r", "60",
+        "--prefer-output", "HDMI-A-1",
+        "--mangoapp", "-f", "-e",
+    ])]);
+}
+
+#[test]
+fn no_mangoapp_leaves_out_the_overlay() {
+    let caps = DisplayCapabilities { max_refresh_rate: 60, max_bpc: 8, ..Default::default() };
+    let args = args(&["--no-mangoapp"]);
+
+    let argv = command_argv(&gamescope_command(&display(Path::new("/nonexistent")), &caps, &args));
+    assert!(!argv.iter().any(|arg| arg == "--mangoapp"), "{:?}", argv);
+}
+
+#[test]
+fn no_expose_wayland_leaves_out_e() {
+    let caps = DisplayCapabilities { max_refresh_rate: 60, max_bpc: 8, ..Default::default() };
+    let args = args(&["--no-expose-wayland"]);
+
+    let argv = command_argv(&gamescope_command(&display(Path::new("/nonexistent")), &caps, &args));
+    assert!(!argv.iter().any(|arg| arg == "-e"), "{:?}", argv);
+    assert!(!build_virtual_output_args(1920, 1080, 60.0, false, &args).iter().any(|arg| arg == "-e"));
+}
+
+#[test]
+fn max_bpc_caps_even_a_forced_bpc() {
+    let caps = DisplayCapabilities { hdr: true, max_bpc: 12, ..Default::default() };
+    assert_eq!(output_bpc(&caps, &args(&["--bpc", "12", "--force-bpc", "--max-bpc", "10"])), Some(10));
+    assert_eq!(output_bpc(&caps, &args(&["--max-bpc", "8"])), Some(8));
+    assert_eq!(output_bpc(&caps, &args(&[])), Some(10));
+}
+
+#[test]
+fn hdmi_pixel_format_falls_back_to_ycbcr420() {
+    let display = display(Path::new("/nonexistent"));
+    let hdmi_2_0 = DisplayCapabilities {
+        max_refresh_rate: 60,
+        max_tmds_mhz: Some(600),
+        ycbcr420_modes: vec![(3840, 2160, 60)],
+        ..Default::default()
+    };
+    assert_eq!(hdmi_pixel_format(&display, &hdmi_2_0, 8, ColorFormat::Auto), Some(false));
+    assert_eq!(hdmi_pixel_format(&display, &hdmi_2_0, 10, ColorFormat::Auto), Some(true));
+    assert_eq!(hdmi_pixel_format(&display, &hdmi_2_0, 10, ColorFormat::Rgb), None);
+
+    // Auto only picks 4:2:0 for modes the EDID lists it for
+    let rgb_only = DisplayCapabilities { ycbcr420_modes: Vec::new(), ..hdmi_2_0.clone() };
+    assert_eq!(hdmi_pixel_format(&display, &rgb_only, 10, ColorFormat::Auto), None);
+    assert_eq!(hdmi_pixel_format(&display, &rgb_only, 10, ColorFormat::Ycbcr420), Some(true));
+
+    // 4K120 at 10-bit on a 40 Gbps FRL link fits without 4:2:0
+    let frl = DisplayCapabilities { max_refresh_rate: 120, max_frl_gbps: Some(40), ..hdmi_2_0 };
+    assert_eq!(hdmi_pixel_format(&display, &frl, 10, ColorFormat::Auto), Some(false));
+}
+
+#[test]
+fn toggles_map_to_gamescope_flags() {
+    let caps = DisplayCapabilities { max_refresh_rate: 60, max_bpc: 8, ..Default::default() };
+    let args = args(&["--grab-cursor", "--immediate-flips", "--force-composition", "--", "--immediate-flips"]);
+
+    let argv = command_argv(&gamescope_command(&display(Path::new("/nonexistent")), &caps, &args));
+    assert!(argv.iter().any(|arg| arg == "--force-grab-cursor"), "{:?}", argv);
+    assert!(argv.iter().any(|arg| arg == "--force-composition"), "{:?}", argv);
+    assert_eq!(argv.iter().filter(|arg| *arg == "--immediate-flips").count(), 1, "{:?}", argv);
+}
+
+#[test]
+fn nested_gamescope_gets_toggles() {
+    let args = args(&["--grab-cursor", "--borderless", "--windowed"]);
+
+    assert_eq!(build_nested_args(2560, 1440, 144, &args), argv(&[
+        "-W", "2560", "-H", "1440", "-r", "144",
+        "--nested-width", "2560", "--nested-height", "1440", "--nested-refresh", "144",
+        "-e", "-b", "--mangoapp", "--force-grab-cursor",
+    ]));
+    assert!(build_virtual_output_args(1920, 1080, 60.0, false, &args).iter().any(|arg| arg == "--force-grab-cursor"));
+}
+
+#[test]
+fn virtual_output_enables_hdr_only_when_asked() {
+    let argv = build_virtual_output_args(1920, 1080, 59.94, true, &args(&[]));
+    assert_eq!(argv[..8], ["--backend", "headless", "-W", "1920", "-H", "1080", "-r", "60"]);
+    assert!(argv.iter().any(|arg| arg == "--hdr-enabled"), "{:?}", argv);
+
+    let argv = build_virtual_output_args(1920, 1080, 60.0, false, &args(&[]));
+    assert!(!argv.iter().any(|arg| arg == "--hdr-enabled"), "{:?}", argv);
+}
+
+#[test]
+fn capabilities_come_from_edid_decode_output() {
+    // Keep the capability cache out of the real home directory
+    let cache = std::env::temp_dir().join(format!("console-mode-launch-cache-{}", std::process::id()));
+    std::env::set_var("XDG_CACHE_HOME", &cache);
+
+    let connector = fake_connector("edid-decode", b"edid bytes");
+    let runner = MockRunner::new()
+        .with_output("edid-decode", 0, include_str!("fixtures/edid-decode/hdr-tv.txt"));
+    let (caps, warnings) = probe_capabilities(&runner, &display(&connector), &args(&["--no-cache"])).unwrap();
+    fs::remove_dir_all(&connector).unwrap();
+    let _ = fs::remove_dir_all(&cache);
+
+    assert_eq!(runner.calls(), [argv(&["edid-decode"])]);
+    assert!(caps.hdr);
+    assert_eq!(caps.max_refresh_rate, 120);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn missing_edid_file_is_an_error() {
+    let runner = MockRunner::new();
+    let result = probe_capabilities(&runner, &display(Path::new("/nonexistent")), &args(&["--display", "HDMI-A-1", "--edid-file", "/nonexistent/edid.bin"]));
+
+    assert!(matches!(result, Err(Error::EdidFileMissing(path)) if path == Path::new("/nonexistent/edid.bin")));
+    assert!(runner.calls().is_empty());
+}
+
+#[test]
+fn edid_file_is_parsed_for_the_chosen_display_only() {
+    // A bare EDID 1.4 base block for a 160x90 cm screen
+    let mut edid = vec![0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+    edid.resize(128, 0);
+    edid[18..23].copy_from_slice(&[1, 4, 0x80, 160, 90]);
+    let connector = fake_connector("edid-file", b"");
+    let edid_file = connector.join("fixed-edid.bin");
+    fs::write(&edid_file, &edid).unwrap();
+    let edid_file = edid_file.to_str().unwrap();
+    let runner = MockRunner::new().with_output("edid-decode", 1, "");
+
+    let (caps, warnings) = probe_capabilities(&runner, &display(&connector), &args(&["--no-cache", "--display", "HDMI-A-1", "--edid-file", edid_file])).unwrap();
+    let (other_caps, other_warnings) = probe_capabilities(&runner, &display(&connector), &args(&["--no-cache", "--display", "DP-1", "--edid-file", edid_file])).unwrap();
+    fs::remove_dir_all(&connector).unwrap();
+
+    assert_eq!(caps.physical_size_mm, Some((1600, 900)));
+    assert_eq!(warnings, ["edid-decode failed (exit status: 1)", "Using built-in EDID parser"]);
+    // Any other display keeps its own (here empty) EDID
+    assert_eq!(other_caps.physical_size_mm, None);
+    assert_eq!(other_warnings, ["EDID file is empty, using defaults"]);
+}
+
+#[test]
+fn failed_edid_decode_falls_back() {
+    let connector = fake_connector("edid-decode-fails", b"not an edid");
+    let runner = MockRunner::new().with_output("edid-decode", 1, "");
+    let (caps, warnings) = probe_capabilities(&runner, &display(&connector), &args(&["--no-cache"])).unwrap();
+    fs::remove_dir_all(&connector).unwrap();
+
+    assert!(!caps.hdr);
+    assert_eq!(warnings, ["edid-decode failed (exit status: 1)", "EDID is invalid, using defaults"]);
+}
+
+#[test]
+fn detection_timings_cover_every_step() {
+    let connector = fake_connector("benchmark", b"edid bytes");
+    let runner = MockRunner::new()
+        .with_output("edid-decode", 0, include_str!("fixtures/edid-decode/hdr-tv.txt"));
+    let timings = time_detection(&runner, &display(&connector)).unwrap();
+    fs::remove_dir_all(&connector).unwrap();
+
+    assert_eq!(runner.calls(), [argv(&["edid-decode"])]);
+    assert_eq!(timings.edid_bytes, 10);
+    assert!(timings.edid_decode.is_some());
+    assert!(timings.parse_edid_decode.is_some());
+}
+
+#[test]
+fn dump_edid_writes_raw_bytes_and_edid_decode_text() {
+    let connector = fake_connector("dump-edid", b"edid bytes");
+    let dump = connector.join("dump.bin");
+    let runner = MockRunner::new()
+        .with_output("edid-decode", 0, include_str!("fixtures/edid-decode/hdr-tv.txt"));
+    let text_path = dump_edid(&runner, &display(&connector), &dump).unwrap();
+
+    assert_eq!(fs::read(&dump).unwrap(), b"edid bytes");
+    assert_eq!(text_path, Some(connector.join("dump.bin.txt")));
+    assert_eq!(fs::read_to_string(connector.join("dump.bin.txt")).unwrap(), include_str!("fixtures/edid-decode/hdr-tv.txt"));
+
+    // An empty EDID is reported rather than dumped as an empty file
+    fs::write(connector.join("edid"), b"").unwrap();
+    let empty_dump = connector.join("empty.bin");
+    assert!(dump_edid(&runner, &display(&connector), &empty_dump).is_err());
+    assert!(!empty_dump.exists());
+    fs::remove_dir_all(&connector).unwrap();
+}
+
+#[test]
+fn post_launch_hook_runs_when_a_signal_stops_the_session() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+
+    // A gamescope that gets console-mode stopped, as systemd or Ctrl+C would
+    let dir = fake_connector("post-launch-signal", b"");
+    let gamescope = dir.join("gamescope");
+    fs::write(&gamescope, "#!/bin/sh\nsleep 0.3\nkill -TERM $PPID\nsleep 5\n").unwrap();
+    fs::set_permissions(&gamescope, fs::Permissions::from_mode(0o755)).unwrap();
+    let result = dir.join("result");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_console-mode"))
+        .args(["--headless", "--no-steam", "--quiet", "--gamescope-bin"])
+        .arg(&gamescope)
+        .arg("--post-launch")
+        .arg(format!("echo \"$CONSOLE_MODE_RESULT\" > '{}'", result.display()))
+        .env("HOME", &dir)
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env("XDG_STATE_HOME", dir.join("state"))
+        .output()
+        .unwrap()
+        .status;
+
+    assert_eq!(status.signal(), Some(libc::SIGTERM));
+    assert_eq!(fs::read_to_string(&result).unwrap(), "failure\n");
+    fs::remove_dir_all(&dir).unwrap();
+}